@@ -0,0 +1,482 @@
+use super::{Event, Min, Max, Ins, Dom, Prop, Mod, FDVar, Propagator, Failure};
+
+use std::collections::hashmap::HashMap;
+use std::rc::{Rc, Weak};
+
+/// how much an AllDifferent prunes
+#[deriving(Show, Clone, PartialEq, Eq)]
+pub enum Consistency {
+    /// removes the values of fixed variables from the others
+    ForwardChecking,
+    /// bounds consistency, with Hall intervals
+    BoundsConsistency,
+    /// domain consistency, with a maximum matching of variables to values
+    DomainConsistency
+}
+
+/// x_i != x_j for all i != j
+pub struct AllDifferent;
+
+impl AllDifferent {
+    pub fn new(model: Rc<Mod>, vars: &[Rc<FDVar>], consistency: Consistency) {
+        AllDiff::new(model, vars.to_vec(), consistency);
+    }
+}
+
+struct AllDiff : Prop {
+    consistency: Consistency
+}
+
+/// bounds of a variable, max excluded, with the ranks of both among all
+/// the bounds
+struct Interval {
+    min: int,
+    max: int,
+    min_rank: int,
+    max_rank: int
+}
+
+impl AllDiff {
+    fn new(model: Rc<Mod>, vars: Vec<Rc<FDVar>>, consistency: Consistency) {
+        let id = model.propagators.borrow().len();
+        let this = AllDiff {
+            model: model.downgrade(),
+            id: id,
+            vars: vars,
+            consistency: consistency
+        };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    fn remove_fixed_values(&self) -> Result<Vec<uint>, Failure> {
+        let mut woken = Vec::new();
+        for (i, x) in self.vars.iter().enumerate() {
+            let v = match x.value() {
+                Some(v) => v,
+                None => continue
+            };
+            for (j, y) in self.vars.iter().enumerate() {
+                if i != j {
+                    woken.push_all(try!(y.remove(v)).as_slice());
+                }
+            }
+        }
+        Ok(woken)
+    }
+
+    /// bounds consistent filtering in O(n log n), after Lopez-Ortiz,
+    /// Quimper, Tromp and van Beek
+    fn filter_bounds(&self) -> Result<Vec<uint>, Failure> {
+        let n = self.vars.len();
+        if n == 0 { return Ok(vec![]); }
+        let mut intervals: Vec<Interval> = self.vars.iter()
+            .map(|x| Interval { min: x.min(), max: x.max() + 1, min_rank: 0, max_rank: 0 })
+            .collect();
+        let mut minsorted: Vec<uint> = range(0, n).collect();
+        let mut maxsorted: Vec<uint> = range(0, n).collect();
+        minsorted.sort_by(|&a, &b| intervals.get(a).min.cmp(&intervals.get(b).min));
+        maxsorted.sort_by(|&a, &b| intervals.get(a).max.cmp(&intervals.get(b).max));
+        let (minsorted, maxsorted) = (minsorted.as_slice(), maxsorted.as_slice());
+        let iv = intervals.as_mut_slice();
+
+        // merges all the bounds, the first and last are sentinels
+        let mut bounds: Vec<int> = Vec::with_capacity(2 * n + 2);
+        let mut last = iv[minsorted[0]].min - 2;
+        bounds.push(last);
+        let (mut i, mut j) = (0, 0);
+        let (mut min, mut max) = (iv[minsorted[0]].min, iv[maxsorted[0]].max);
+        loop {
+            if i < n && min <= max {
+                if min != last {
+                    last = min;
+                    bounds.push(min);
+                }
+                iv[minsorted[i]].min_rank = bounds.len() as int - 1;
+                i += 1;
+                if i < n { min = iv[minsorted[i]].min; }
+            } else {
+                if max != last {
+                    last = max;
+                    bounds.push(max);
+                }
+                iv[maxsorted[j]].max_rank = bounds.len() as int - 1;
+                j += 1;
+                if j == n { break; }
+                max = iv[maxsorted[j]].max;
+            }
+        }
+        bounds.push(last + 2);
+
+        try!(filter_lower(iv, maxsorted, bounds.as_slice()));
+        try!(filter_upper(iv, minsorted, bounds.as_slice()));
+        let mut woken = Vec::new();
+        for (x, interval) in self.vars.iter().zip(iv.iter()) {
+            woken.push_all(try!(x.set_min(interval.min)).as_slice());
+            woken.push_all(try!(x.set_max(interval.max - 1)).as_slice());
+        }
+        Ok(woken)
+    }
+
+    /// domain consistent filtering after Régin: a value is kept if it
+    /// belongs to some maximum matching of the variables to the values
+    fn filter_domains(&self) -> Result<Vec<uint>, Failure> {
+        let n = self.vars.len();
+        // bipartite graph of the variables and the values of their domains
+        let mut values: Vec<int> = Vec::new();
+        let mut index: HashMap<int, uint> = HashMap::new();
+        let mut edges: Vec<Vec<uint>> = Vec::with_capacity(n);
+        for x in self.vars.iter() {
+            let mut adjacent = Vec::with_capacity(x.size());
+            for v in x.values() {
+                let next = values.len();
+                let k = *index.find_or_insert(v, next);
+                if k == next { values.push(v); }
+                adjacent.push(k);
+            }
+            edges.push(adjacent);
+        }
+        let m = values.len();
+        let matched = try!(maximum_matching(edges.as_slice(), m));
+
+        // residual graph, variables are nodes 0 to n - 1 and values n to
+        // n + m - 1, matched edges go from variables to values and the
+        // others from values to variables
+        let mut succ: Vec<Vec<uint>> = Vec::from_elem(n + m, Vec::new());
+        let mut free = Vec::from_elem(m, true);
+        for (i, adjacent) in edges.iter().enumerate() {
+            let k = *matched.get(i);
+            *free.get_mut(k) = false;
+            succ.get_mut(i).push(n + k);
+            for &v in adjacent.iter() {
+                if v != k { succ.get_mut(n + v).push(i); }
+            }
+        }
+
+        // edges on an alternating path from a free value are kept
+        let mut reached = Vec::from_elem(n + m, false);
+        let mut stack: Vec<uint> = range(0, m).filter(|&v| *free.get(v)).map(|v| n + v).collect();
+        for &node in stack.iter() { *reached.get_mut(node) = true; }
+        loop {
+            let node = match stack.pop() {
+                Some(node) => node,
+                None => break
+            };
+            for &next in succ.get(node).iter() {
+                if !*reached.get(next) {
+                    *reached.get_mut(next) = true;
+                    stack.push(next);
+                }
+            }
+        }
+
+        // and so are edges on an alternating cycle
+        let component = components(succ.as_slice());
+        let mut woken = Vec::new();
+        for (i, adjacent) in edges.iter().enumerate() {
+            for &v in adjacent.iter() {
+                if v != *matched.get(i) && !*reached.get(n + v)
+                    && *component.get(i) != *component.get(n + v) {
+                    woken.push_all(try!(self.vars.get(i).remove(*values.get(v))).as_slice());
+                }
+            }
+        }
+        Ok(woken)
+    }
+}
+
+impl Propagator for AllDiff {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        let mut events = Vec::new();
+        for x in self.vars.iter() {
+            match self.consistency {
+                ForwardChecking => events.push((x.id, Ins)),
+                BoundsConsistency => {
+                    events.push((x.id, Min));
+                    events.push((x.id, Max));
+                },
+                DomainConsistency => events.push((x.id, Dom))
+            }
+        }
+        events
+    }
+
+    fn priority(&self) -> uint {
+        match self.consistency {
+            ForwardChecking => 0,
+            BoundsConsistency => 1,
+            DomainConsistency => 2
+        }
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        match self.consistency {
+            ForwardChecking => self.remove_fixed_values(),
+            BoundsConsistency => self.filter_bounds(),
+            DomainConsistency => self.filter_domains()
+        }
+    }
+}
+
+/// raises the minimums above the Hall intervals, visiting the variables by
+/// increasing max
+fn filter_lower(iv: &mut [Interval], maxsorted: &[uint], bounds: &[int]) -> Result<(), Failure> {
+    let size = bounds.len();
+    let mut t = Vec::from_elem(size, 0i);
+    let mut d = Vec::from_elem(size, 0i);
+    let mut h = Vec::from_elem(size, 0i);
+    let (t, d, h) = (t.as_mut_slice(), d.as_mut_slice(), h.as_mut_slice());
+    for i in range(1, size) {
+        t[i] = i as int - 1;
+        h[i] = i as int - 1;
+        d[i] = bounds[i] - bounds[i - 1];
+    }
+    for &k in maxsorted.iter() {
+        let (x, y) = (iv[k].min_rank, iv[k].max_rank);
+        let mut z = path_max(t, x + 1);
+        let j = t[z as uint];
+        d[z as uint] -= 1;
+        if d[z as uint] == 0 {
+            t[z as uint] = z + 1;
+            z = path_max(t, z + 1);
+            t[z as uint] = j;
+        }
+        path_set(t, x + 1, z, z);
+        let hall = bounds[z as uint] - bounds[y as uint];
+        if d[z as uint] < hall { return Err(Failure); }
+        if h[x as uint] > x {
+            let w = path_max(h, h[x as uint]);
+            iv[k].min = bounds[w as uint];
+            path_set(h, x, w, w);
+        }
+        if d[z as uint] == hall {
+            path_set(h, h[y as uint], j - 1, y);
+            h[y as uint] = j - 1;
+        }
+    }
+    Ok(())
+}
+
+/// lowers the maximums below the Hall intervals, visiting the variables by
+/// decreasing min
+fn filter_upper(iv: &mut [Interval], minsorted: &[uint], bounds: &[int]) -> Result<(), Failure> {
+    let size = bounds.len();
+    let mut t = Vec::from_elem(size, 0i);
+    let mut d = Vec::from_elem(size, 0i);
+    let mut h = Vec::from_elem(size, 0i);
+    let (t, d, h) = (t.as_mut_slice(), d.as_mut_slice(), h.as_mut_slice());
+    for i in range(0, size - 1) {
+        t[i] = i as int + 1;
+        h[i] = i as int + 1;
+        d[i] = bounds[i + 1] - bounds[i];
+    }
+    for &k in minsorted.iter().rev() {
+        let (x, y) = (iv[k].max_rank, iv[k].min_rank);
+        let mut z = path_min(t, x - 1);
+        let j = t[z as uint];
+        d[z as uint] -= 1;
+        if d[z as uint] == 0 {
+            t[z as uint] = z - 1;
+            z = path_min(t, z - 1);
+            t[z as uint] = j;
+        }
+        path_set(t, x - 1, z, z);
+        let hall = bounds[y as uint] - bounds[z as uint];
+        if d[z as uint] < hall { return Err(Failure); }
+        if h[x as uint] < x {
+            let w = path_min(h, h[x as uint]);
+            iv[k].max = bounds[w as uint];
+            path_set(h, x, w, w);
+        }
+        if d[z as uint] == hall {
+            path_set(h, h[y as uint], j + 1, y);
+            h[y as uint] = j + 1;
+        }
+    }
+    Ok(())
+}
+
+/// points all the nodes on the path from start to end to target
+fn path_set(t: &mut [int], start: int, end: int, target: int) {
+    let mut k = start;
+    while k != end {
+        let next = t[k as uint];
+        t[k as uint] = target;
+        k = next;
+    }
+}
+
+fn path_min(t: &[int], i: int) -> int {
+    let mut i = i;
+    while t[i as uint] < i { i = t[i as uint]; }
+    i
+}
+
+fn path_max(t: &[int], i: int) -> int {
+    let mut i = i;
+    while t[i as uint] > i { i = t[i as uint]; }
+    i
+}
+
+/// value matched to each variable in a maximum matching of the bipartite
+/// graph, fails if some variable cannot be matched
+fn maximum_matching(edges: &[Vec<uint>], values: uint) -> Result<Vec<uint>, Failure> {
+    let mut owner: Vec<Option<uint>> = Vec::from_elem(values, None);
+    for i in range(0, edges.len()) {
+        let mut seen = Vec::from_elem(values, false);
+        if !augment(i, edges, owner.as_mut_slice(), seen.as_mut_slice()) {
+            return Err(Failure);
+        }
+    }
+    let mut matched = Vec::from_elem(edges.len(), 0u);
+    for (v, x) in owner.iter().enumerate() {
+        match *x {
+            Some(i) => *matched.get_mut(i) = v,
+            None => ()
+        }
+    }
+    Ok(matched)
+}
+
+/// looks for an augmenting path from variable i, depth first with an
+/// explicit stack so that large domains cannot overflow the call stack
+fn augment(i: uint, edges: &[Vec<uint>], owner: &mut [Option<uint>], seen: &mut [bool]) -> bool {
+    // variables on the path with the next edge to try, and the values
+    // leading from each of them to the next one
+    let mut path = vec![(i, 0u)];
+    let mut via: Vec<uint> = Vec::new();
+    loop {
+        let (x, k) = match path.last() {
+            Some(&last) => last,
+            None => return false
+        };
+        if k == edges[x].len() {
+            path.pop();
+            via.pop();
+            continue;
+        }
+        *path.mut_last().unwrap() = (x, k + 1);
+        let v = *edges[x].get(k);
+        if seen[v] { continue; }
+        seen[v] = true;
+        match owner[v] {
+            Some(j) => {
+                via.push(v);
+                path.push((j, 0));
+            },
+            None => {
+                // each variable on the path takes the value of the next one
+                owner[v] = Some(x);
+                for (&(y, _), &w) in path.iter().zip(via.iter()) {
+                    owner[w] = Some(y);
+                }
+                return true;
+            }
+        }
+    }
+}
+
+/// strongly connected component of each node, with Tarjan's algorithm
+fn components(succ: &[Vec<uint>]) -> Vec<uint> {
+    let mut tarjan = Tarjan {
+        succ: succ,
+        index: Vec::from_elem(succ.len(), None),
+        low: Vec::from_elem(succ.len(), 0),
+        stack: Vec::new(),
+        on_stack: Vec::from_elem(succ.len(), false),
+        component: Vec::from_elem(succ.len(), 0),
+        visited: 0,
+        components: 0
+    };
+    for node in range(0, succ.len()) {
+        if tarjan.index.get(node).is_none() {
+            tarjan.visit(node);
+        }
+    }
+    tarjan.component
+}
+
+struct Tarjan<'a> {
+    succ: &'a [Vec<uint>],
+    index: Vec<Option<uint>>,
+    low: Vec<uint>,
+    stack: Vec<uint>,
+    on_stack: Vec<bool>,
+    component: Vec<uint>,
+    visited: uint,
+    components: uint
+}
+
+impl<'a> Tarjan<'a> {
+    /// depth first search from root, with an explicit stack of the nodes
+    /// being visited and the position of their next successor
+    fn visit(&mut self, root: uint) {
+        let succ = self.succ;
+        let mut calls = vec![(root, 0u)];
+        self.open(root);
+        loop {
+            let (node, k) = match calls.last() {
+                Some(&call) => call,
+                None => return
+            };
+            if k < succ[node].len() {
+                *calls.mut_last().unwrap() = (node, k + 1);
+                let next = *succ[node].get(k);
+                match *self.index.get(next) {
+                    None => {
+                        self.open(next);
+                        calls.push((next, 0));
+                    },
+                    Some(index) if *self.on_stack.get(next) => self.lower(node, index),
+                    Some(_) => ()
+                }
+            } else {
+                calls.pop();
+                self.close(node);
+                match calls.last() {
+                    Some(&(parent, _)) => {
+                        let low = *self.low.get(node);
+                        self.lower(parent, low);
+                    },
+                    None => ()
+                }
+            }
+        }
+    }
+
+    fn open(&mut self, node: uint) {
+        *self.index.get_mut(node) = Some(self.visited);
+        *self.low.get_mut(node) = self.visited;
+        self.visited += 1;
+        self.stack.push(node);
+        *self.on_stack.get_mut(node) = true;
+    }
+
+    fn lower(&mut self, node: uint, low: uint) {
+        if low < *self.low.get(node) { *self.low.get_mut(node) = low; }
+    }
+
+    /// pops the component of node once all its successors are visited
+    fn close(&mut self, node: uint) {
+        if Some(*self.low.get(node)) == *self.index.get(node) {
+            loop {
+                let top = self.stack.pop().unwrap();
+                *self.on_stack.get_mut(top) = false;
+                *self.component.get_mut(top) = self.components;
+                if top == node { break; }
+            }
+            self.components += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,154 @@
+use super::super::{Model, Var, FDVar, LeXC, GeXC, NeqXC, InXS, AllDifferent, ForwardChecking,
+    BoundsConsistency, DomainConsistency};
+use super::{components, maximum_matching};
+
+use std::rc::Rc;
+
+fn bounds(vars: &[Rc<FDVar>]) -> Vec<(int, int)> {
+    vars.iter().map(|x| (x.min(), x.max())).collect()
+}
+
+#[test]
+fn forward_checking_removes_fixed_values() {
+    let m = Model::new();
+    let vars: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 1, 3, format!("x{}", i).as_slice())).collect();
+    AllDifferent::new(m.clone(), vars.as_slice(), ForwardChecking);
+    assert_eq!(bounds(vars.as_slice()), vec![(1, 3), (1, 3), (1, 3)]);
+    LeXC::new(m.clone(), vars.get(0).clone(), 1);
+    assert_eq!(bounds(vars.as_slice()), vec![(1, 1), (2, 3), (2, 3)]);
+    GeXC::new(m.clone(), vars.get(1).clone(), 3);
+    assert_eq!(bounds(vars.as_slice()), vec![(1, 1), (3, 3), (2, 2)]);
+}
+
+#[test]
+fn forward_checking_fails_on_equal_values() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 4, 4, "x");
+    let y = Var::new(m.clone(), 4, 4, "y");
+    AllDifferent::new(m.clone(), &[x, y], ForwardChecking);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn bounds_consistency_uses_hall_intervals() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 1, 2, "x");
+    let y = Var::new(m.clone(), 1, 2, "y");
+    let z = Var::new(m.clone(), 1, 3, "z");
+    let w = Var::new(m.clone(), 2, 5, "w");
+    let vars = [x, y, z, w];
+    AllDifferent::new(m.clone(), vars.as_slice(), BoundsConsistency);
+    assert_eq!(bounds(vars.as_slice()), vec![(1, 2), (1, 2), (3, 3), (4, 5)]);
+}
+
+#[test]
+fn bounds_consistency_tightens_max() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 3, 4, "x");
+    let y = Var::new(m.clone(), 3, 4, "y");
+    let z = Var::new(m.clone(), 0, 4, "z");
+    let vars = [x, y, z];
+    AllDifferent::new(m.clone(), vars.as_slice(), BoundsConsistency);
+    assert_eq!(bounds(vars.as_slice()), vec![(3, 4), (3, 4), (0, 2)]);
+}
+
+#[test]
+fn bounds_consistency_detects_pigeonholes() {
+    let m = Model::new();
+    let vars: Vec<Rc<FDVar>> = range(0, 4)
+        .map(|i| Var::new(m.clone(), 0, 9, format!("x{}", i).as_slice())).collect();
+    AllDifferent::new(m.clone(), vars.as_slice(), BoundsConsistency);
+    assert!(!m.is_failed());
+    for x in vars.iter().skip(1) {
+        LeXC::new(m.clone(), x.clone(), 2);
+    }
+    assert_eq!(vars.get(0).min(), 3);
+    LeXC::new(m.clone(), vars.get(0).clone(), 2);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn domain_consistency_removes_inner_values() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 9, "x");
+    let y = Var::new(m.clone(), 0, 9, "y");
+    let z = Var::new(m.clone(), 0, 9, "z");
+    InXS::new(m.clone(), x.clone(), &[1, 5]);
+    InXS::new(m.clone(), y.clone(), &[1, 5]);
+    AllDifferent::new(m.clone(), &[x.clone(), y.clone(), z.clone()], DomainConsistency);
+    assert_eq!(z.size(), 8);
+    assert!(!z.contains(1) && !z.contains(5));
+    assert_eq!((z.min(), z.max()), (0, 9));
+}
+
+#[test]
+fn domain_consistency_finds_forced_values() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 1, 3, "x");
+    let y = Var::new(m.clone(), 1, 3, "y");
+    let z = Var::new(m.clone(), 1, 3, "z");
+    NeqXC::new(m.clone(), x.clone(), 2);
+    NeqXC::new(m.clone(), y.clone(), 2);
+    AllDifferent::new(m.clone(), &[x.clone(), y.clone(), z.clone()], DomainConsistency);
+    assert_eq!(z.value(), Some(2));
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone(), z.clone()]), 2);
+    NeqXC::new(m.clone(), x.clone(), 3);
+    assert_eq!((x.value(), y.value()), (Some(1), Some(3)));
+}
+
+#[test]
+fn domain_consistency_fails_without_matching() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 9, "x");
+    let y = Var::new(m.clone(), 0, 9, "y");
+    let z = Var::new(m.clone(), 0, 9, "z");
+    AllDifferent::new(m.clone(), &[x.clone(), y.clone(), z.clone()], DomainConsistency);
+    InXS::new(m.clone(), x.clone(), &[3, 7]);
+    InXS::new(m.clone(), y.clone(), &[3, 7]);
+    assert!(!z.contains(3) && !z.contains(7));
+    InXS::new(m.clone(), z.clone(), &[3, 7, 8]);
+    assert_eq!(z.value(), Some(8));
+    InXS::new(m.clone(), y.clone(), &[3]);
+    assert_eq!(x.value(), Some(7));
+    assert!(!m.is_failed());
+    InXS::new(m.clone(), x.clone(), &[3]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn all_levels_count_permutations() {
+    for &consistency in [ForwardChecking, BoundsConsistency, DomainConsistency].iter() {
+        let m = Model::new();
+        let vars: Vec<Rc<FDVar>> = range(0, 4)
+            .map(|i| Var::new(m.clone(), 0, 3, format!("x{}", i).as_slice())).collect();
+        AllDifferent::new(m.clone(), vars.as_slice(), consistency);
+        assert_eq!(m.count_solutions(vars), 24);
+    }
+}
+
+#[test]
+fn finds_strongly_connected_components() {
+    let succ = vec![vec![1], vec![2], vec![0, 3], vec![4], vec![3], vec![]];
+    let component = components(succ.as_slice());
+    assert!(component.get(0) == component.get(1) && component.get(1) == component.get(2));
+    assert!(component.get(3) == component.get(4) && component.get(0) != component.get(3));
+    assert!(component.get(5) != component.get(0) && component.get(5) != component.get(3));
+}
+
+#[test]
+fn long_paths_do_not_recurse() {
+    let n = 100000u;
+    // a single cycle through all the nodes
+    let succ: Vec<Vec<uint>> = range(0, n).map(|i| vec![(i + 1) % n]).collect();
+    let component = components(succ.as_slice());
+    assert!(component.iter().all(|c| c == component.get(0)));
+    // the last variable takes 0 by shifting all the others by one value
+    let n = 10000u;
+    let edges: Vec<Vec<uint>> = range(0, n)
+        .map(|i| if i < n - 1 { vec![i, i + 1] } else { vec![0] })
+        .collect();
+    let matched = maximum_matching(edges.as_slice(), n).unwrap();
+    assert_eq!(*matched.get(n - 1), 0);
+    assert!(range(0, n - 1).all(|i| *matched.get(i) == i + 1));
+}
@@ -0,0 +1,219 @@
+use super::{Event, Min, Max, Prop, Mod, FDVar, Propagator, Failure, div_ceil};
+use super::scheduling::Task;
+
+use std::cmp::{min, max};
+use std::rc::{Rc, Weak};
+
+/// tasks starting at S_i for D_i units of time, each using C_i units of a
+/// resource of capacity C at any time
+pub struct Cumulative;
+
+impl Cumulative {
+    /// filtered with the time-table, the profile of compulsory parts
+    pub fn new(model: Rc<Mod>, starts: &[Rc<FDVar>], durations: &[int], demands: &[int],
+               capacity: int) {
+        CumulativeProp::new(model, starts, durations, demands, capacity, false);
+    }
+
+    /// filtered with both the time-table and edge finding
+    pub fn with_edge_finding(model: Rc<Mod>, starts: &[Rc<FDVar>], durations: &[int],
+                             demands: &[int], capacity: int) {
+        CumulativeProp::new(model, starts, durations, demands, capacity, true);
+    }
+}
+
+struct CumulativeProp : Prop {
+    durations: Vec<int>,
+    demands: Vec<int>,
+    capacity: int,
+    edge_finding: bool
+}
+
+impl CumulativeProp {
+    fn new(model: Rc<Mod>, starts: &[Rc<FDVar>], durations: &[int], demands: &[int],
+           capacity: int, edge_finding: bool) {
+        assert!(starts.len() == durations.len() && starts.len() == demands.len(),
+                "one duration and one demand per task");
+        let id = model.propagators.borrow().len();
+        let this = CumulativeProp {
+            model: model.downgrade(),
+            id: id,
+            vars: starts.to_vec(),
+            durations: durations.to_vec(),
+            demands: demands.to_vec(),
+            capacity: capacity,
+            edge_finding: edge_finding
+        };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    /// the tasks using the resource
+    fn tasks(&self) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        for (i, x) in self.vars.iter().enumerate() {
+            let (duration, demand) = (*self.durations.get(i), *self.demands.get(i));
+            if duration > 0 && demand > 0 {
+                tasks.push(Task::new(i, &**x, duration, demand));
+            }
+        }
+        tasks
+    }
+
+    /// moves the earliest start of task i to est and its latest completion
+    /// to lct
+    fn tighten(&self, task: &Task, est: int, lct: int) -> Result<Vec<uint>, Failure> {
+        let x = self.vars.get(task.index);
+        let mut woken = try!(x.set_min(est));
+        woken.push_all(try!(x.set_max(lct - task.duration)).as_slice());
+        Ok(woken)
+    }
+}
+
+impl Propagator for CumulativeProp {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        let mut events = Vec::new();
+        for x in self.vars.iter() {
+            events.push((x.id, Min));
+            events.push((x.id, Max));
+        }
+        events
+    }
+
+    fn priority(&self) -> uint {
+        2
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let tasks = self.tasks();
+        if tasks.iter().any(|t| t.demand > self.capacity) { return Err(Failure); }
+        let mut woken = Vec::new();
+        let (ests, lcts) = try!(time_table(tasks.as_slice(), self.capacity));
+        for (k, task) in tasks.iter().enumerate() {
+            woken.push_all(try!(self.tighten(task, *ests.get(k), *lcts.get(k))).as_slice());
+        }
+        if self.edge_finding {
+            // bounds changed by the time-table are used right away
+            let tasks = self.tasks();
+            let ests = try!(edge_finding(tasks.as_slice(), self.capacity));
+            let mirrored: Vec<Task> = tasks.iter().map(|t| t.mirror()).collect();
+            let lcts = try!(edge_finding(mirrored.as_slice(), self.capacity));
+            for (k, task) in tasks.iter().enumerate() {
+                let (est, lct) = (*ests.get(k), -*lcts.get(k));
+                woken.push_all(try!(self.tighten(task, est, lct)).as_slice());
+            }
+        }
+        Ok(woken)
+    }
+}
+
+/// earliest starts and latest completions of the tasks so that none
+/// overlaps a part of the profile of compulsory parts where it does not
+/// fit, fails if the profile exceeds the capacity
+fn time_table(tasks: &[Task], capacity: int) -> Result<(Vec<int>, Vec<int>), Failure> {
+    // height changes at the bounds of the compulsory parts, decreases first
+    let mut changes: Vec<(int, int)> = Vec::new();
+    for t in tasks.iter() {
+        if t.lst() < t.ect() {
+            changes.push((t.lst(), t.demand));
+            changes.push((t.ect(), -t.demand));
+        }
+    }
+    changes.sort();
+    let mut profile: Vec<(int, int, int)> = Vec::new();
+    let mut height = 0;
+    for k in range(0, changes.len()) {
+        let (time, delta) = *changes.get(k);
+        height += delta;
+        if height > capacity { return Err(Failure); }
+        if k + 1 < changes.len() {
+            let (next, _) = *changes.get(k + 1);
+            if next > time && height > 0 {
+                profile.push((time, next, height));
+            }
+        }
+    }
+
+    let mut ests = Vec::with_capacity(tasks.len());
+    let mut lcts = Vec::with_capacity(tasks.len());
+    for t in tasks.iter() {
+        // height of a segment without the compulsory part of t
+        let others = |&(start, end, h): &(int, int, int)| -> int {
+            if t.lst() <= start && end <= t.ect() { h - t.demand } else { h }
+        };
+        let mut est = t.est;
+        for segment in profile.iter() {
+            let &(start, end, _) = segment;
+            if end <= est { continue; }
+            if start >= est + t.duration { break; }
+            if others(segment) + t.demand > capacity { est = end; }
+        }
+        let mut lct = t.lct;
+        for segment in profile.iter().rev() {
+            let &(start, end, _) = segment;
+            if start >= lct { continue; }
+            if end <= lct - t.duration { break; }
+            if others(segment) + t.demand > capacity { lct = start; }
+        }
+        ests.push(est);
+        lcts.push(lct);
+    }
+    Ok((ests, lcts))
+}
+
+/// earliest starts of the tasks after edge finding, a task has to end after
+/// a set of tasks ending before it when starting before their end would
+/// need more energy than available, fails when the energy of tasks exceeds
+/// what their window offers
+fn edge_finding(tasks: &[Task], capacity: int) -> Result<Vec<int>, Failure> {
+    let mut ests: Vec<int> = tasks.iter().map(|t| t.est).collect();
+    let mut ends: Vec<int> = tasks.iter().map(|t| t.lct).collect();
+    ends.sort();
+    ends.dedup();
+    // by decreasing earliest start
+    let mut order: Vec<uint> = range(0, tasks.len()).collect();
+    order.sort_by(|&a, &b| tasks[b].est.cmp(&tasks[a].est));
+    for &b in ends.iter() {
+        let omega: Vec<&Task> = order.iter().map(|&j| &tasks[j]).filter(|t| t.lct <= b).collect();
+        // sets of the tasks of omega starting at a or later, for decreasing a
+        let mut sets: Vec<(int, int)> = Vec::new();
+        let mut energy = 0;
+        for (k, t) in omega.iter().enumerate() {
+            energy += t.energy();
+            if k + 1 == omega.len() || omega.get(k + 1).est != t.est {
+                if energy > capacity * (b - t.est) { return Err(Failure); }
+                sets.push((t.est, energy));
+            }
+        }
+        for (i, task) in tasks.iter().enumerate() {
+            if task.lct <= b { continue; }
+            // best bound given by a subset of the set considered
+            let mut update = None;
+            for &(a, energy) in sets.iter() {
+                let rest = energy - (capacity - task.demand) * (b - a);
+                if rest > 0 {
+                    let bound = a + div_ceil(rest, task.demand);
+                    update = Some(match update { Some(u) => max(u, bound), None => bound });
+                }
+                if energy + task.energy() > capacity * (b - min(a, task.est)) {
+                    match update {
+                        Some(u) if u > *ests.get(i) => *ests.get_mut(i) = u,
+                        _ => ()
+                    }
+                }
+            }
+        }
+    }
+    Ok(ests)
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,97 @@
+use super::super::{Model, Var, FDVar, Cumulative};
+use super::super::scheduling::task;
+use super::{time_table, edge_finding};
+
+use std::rc::Rc;
+
+#[test]
+fn time_table_pushes_after_compulsory_part() {
+    let m = Model::new();
+    let a = Var::new(m.clone(), 0, 0, "a");
+    let b = Var::new(m.clone(), 0, 10, "b");
+    Cumulative::new(m.clone(), &[a, b.clone()], &[5, 3], &[2, 1], 2);
+    assert_eq!((b.min(), b.max()), (5, 10));
+}
+
+#[test]
+fn time_table_pushes_before_compulsory_part() {
+    let m = Model::new();
+    let a = Var::new(m.clone(), 5, 5, "a");
+    let b = Var::new(m.clone(), 0, 8, "b");
+    Cumulative::new(m.clone(), &[a, b.clone()], &[5, 3], &[2, 1], 2);
+    assert_eq!((b.min(), b.max()), (0, 2));
+}
+
+#[test]
+fn time_table_fails_on_overload() {
+    let m = Model::new();
+    let a = Var::new(m.clone(), 0, 2, "a");
+    let b = Var::new(m.clone(), 1, 3, "b");
+    Cumulative::new(m.clone(), &[a, b], &[4, 4], &[2, 2], 3);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn time_table_ignores_fitting_tasks() {
+    let m = Model::new();
+    let a = Var::new(m.clone(), 0, 0, "a");
+    let b = Var::new(m.clone(), 0, 10, "b");
+    let c = Var::new(m.clone(), 0, 10, "c");
+    Cumulative::new(m.clone(), &[a, b.clone(), c.clone()], &[5, 3, 2], &[1, 1, 0], 2);
+    assert_eq!((b.min(), c.min()), (0, 0));
+}
+
+#[test]
+fn edge_finding_finds_precedences() {
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = vec![Var::new(m.clone(), 0, 3, "a"),
+                                      Var::new(m.clone(), 0, 3, "b"),
+                                      Var::new(m.clone(), 0, 10, "c")];
+    Cumulative::new(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 1], 1);
+    assert_eq!(starts.get(2).min(), 0);
+    Cumulative::with_edge_finding(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 1], 1);
+    assert_eq!(starts.get(2).min(), 4);
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn edge_finding_detects_overload() {
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 0, 3, format!("s{}", i).as_slice()))
+        .collect();
+    Cumulative::new(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 1], 1);
+    assert!(!m.is_failed());
+    Cumulative::with_edge_finding(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 1], 1);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn schedules_tasks() {
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 0, 4, format!("s{}", i).as_slice()))
+        .collect();
+    Cumulative::new(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 1], 1);
+    assert_eq!(m.count_solutions(starts.clone()), 6);
+    // two tasks fit side by side with a capacity of 2
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 0, 2, format!("s{}", i).as_slice()))
+        .collect();
+    Cumulative::with_edge_finding(m.clone(), starts.as_slice(), &[2, 2, 2], &[1, 1, 2], 2);
+    for solution in m.solutions(starts.clone()) {
+        let (a, b, c) = (*solution.get(&"s0".to_string()), *solution.get(&"s1".to_string()),
+                         *solution.get(&"s2".to_string()));
+        assert!(c + 2 <= a || a + 2 <= c);
+        assert!(c + 2 <= b || b + 2 <= c);
+    }
+}
+
+#[test]
+fn edge_finding_on_tasks() {
+    let tasks = [task(0, 5, 2, 1), task(0, 5, 2, 1), task(0, 12, 2, 1)];
+    assert_eq!(edge_finding(tasks.as_slice(), 1), Ok(vec![0, 0, 4]));
+    let (ests, lcts) = time_table(tasks.as_slice(), 1).unwrap();
+    assert_eq!((ests, lcts), (vec![0, 0, 0], vec![5, 5, 12]));
+}
@@ -0,0 +1,324 @@
+use super::{Event, Min, Max, Prop, Mod, FDVar, Propagator, Failure};
+use super::scheduling::Task;
+
+use std::cmp::{min, max};
+use std::int;
+use std::rc::{Rc, Weak};
+
+/// tasks starting at S_i for D_i units of time, no two at the same time
+pub struct Disjunctive;
+
+impl Disjunctive {
+    pub fn new(model: Rc<Mod>, starts: &[Rc<FDVar>], durations: &[int]) {
+        DisjunctiveProp::new(model, starts, durations);
+    }
+}
+
+/// less than any completion time, far enough from int::MIN to add durations
+static NONE: int = int::MIN / 2;
+
+/// node of a Theta-Lambda tree, for the tasks of its leaves in Theta and
+/// in Lambda, the gray ones
+#[deriving(Clone)]
+struct Node {
+    /// total duration of the tasks in Theta
+    sum: int,
+    /// earliest completion time of the tasks in Theta
+    ect: int,
+    /// total duration with at most one gray task
+    gray_sum: int,
+    /// earliest completion time with at most one gray task
+    gray_ect: int,
+    /// gray leaf used by gray_sum, if any
+    sum_leaf: Option<uint>,
+    /// gray leaf used by gray_ect, if any
+    ect_leaf: Option<uint>
+}
+
+static EMPTY: Node = Node {
+    sum: 0, ect: NONE, gray_sum: 0, gray_ect: NONE, sum_leaf: None, ect_leaf: None
+};
+
+/// the greatest value, preferring the ones using a gray leaf on ties
+fn greatest(options: &[(int, Option<uint>)]) -> (int, Option<uint>) {
+    let mut best = options[0];
+    for &(value, leaf) in options.iter().skip(1) {
+        let (v, l) = best;
+        if value > v || (value == v && l.is_none() && leaf.is_some()) {
+            best = (value, leaf);
+        }
+    }
+    best
+}
+
+/// balanced binary tree with the tasks as leaves in increasing est order,
+/// computing the earliest completion time of sets of tasks in O(log n)
+struct ThetaLambda {
+    /// number of leaves, a power of 2
+    size: uint,
+    /// root at 1, children of i at 2i and 2i + 1
+    nodes: Vec<Node>
+}
+
+impl ThetaLambda {
+    fn new(leaves: uint) -> ThetaLambda {
+        let mut size = 1;
+        while size < leaves { size *= 2; }
+        ThetaLambda { size: size, nodes: Vec::from_elem(2 * size, EMPTY) }
+    }
+
+    /// adds the task at leaf to Theta
+    fn insert(&mut self, leaf: uint, task: &Task) {
+        self.set(leaf, Node {
+            sum: task.duration,
+            ect: task.ect(),
+            gray_sum: task.duration,
+            gray_ect: task.ect(),
+            sum_leaf: None,
+            ect_leaf: None
+        });
+    }
+
+    /// moves the task at leaf to Lambda
+    fn gray(&mut self, leaf: uint, task: &Task) {
+        self.set(leaf, Node {
+            sum: 0,
+            ect: NONE,
+            gray_sum: task.duration,
+            gray_ect: task.ect(),
+            sum_leaf: Some(leaf),
+            ect_leaf: Some(leaf)
+        });
+    }
+
+    fn remove(&mut self, leaf: uint) {
+        self.set(leaf, EMPTY);
+    }
+
+    fn set(&mut self, leaf: uint, node: Node) {
+        let mut i = self.size + leaf;
+        *self.nodes.get_mut(i) = node;
+        while i > 1 {
+            i /= 2;
+            let node = {
+                let (l, r) = (self.nodes.get(2 * i), self.nodes.get(2 * i + 1));
+                let (gray_sum, sum_leaf) = greatest(&[(l.gray_sum + r.sum, l.sum_leaf),
+                                                      (l.sum + r.gray_sum, r.sum_leaf)]);
+                let (gray_ect, ect_leaf) = greatest(&[(r.gray_ect, r.ect_leaf),
+                                                      (l.ect + r.gray_sum, r.sum_leaf),
+                                                      (l.gray_ect + r.sum, l.ect_leaf)]);
+                Node {
+                    sum: l.sum + r.sum,
+                    ect: max(r.ect, l.ect + r.sum),
+                    gray_sum: gray_sum,
+                    gray_ect: gray_ect,
+                    sum_leaf: sum_leaf,
+                    ect_leaf: ect_leaf
+                }
+            };
+            *self.nodes.get_mut(i) = node;
+        }
+    }
+
+    /// earliest completion time of Theta
+    fn ect(&self) -> int {
+        self.nodes.get(1).ect
+    }
+
+    /// earliest completion time of Theta and at most one gray task
+    fn gray_ect(&self) -> int {
+        self.nodes.get(1).gray_ect
+    }
+
+    /// the gray leaf giving gray_ect
+    fn responsible(&self) -> Option<uint> {
+        self.nodes.get(1).ect_leaf
+    }
+}
+
+/// rank of each task in increasing est order, and the task of each rank
+fn leaves(tasks: &[Task]) -> (Vec<uint>, Vec<uint>) {
+    let mut order: Vec<uint> = range(0, tasks.len()).collect();
+    order.sort_by(|&a, &b| tasks[a].est.cmp(&tasks[b].est));
+    let mut rank = Vec::from_elem(tasks.len(), 0u);
+    for (k, &i) in order.iter().enumerate() {
+        *rank.get_mut(i) = k;
+    }
+    (rank, order)
+}
+
+/// tasks sorted by key
+fn sorted_by(tasks: &[Task], key: |&Task| -> int) -> Vec<uint> {
+    let keys: Vec<int> = tasks.iter().map(|t| key(t)).collect();
+    let mut order: Vec<uint> = range(0, tasks.len()).collect();
+    order.sort_by(|&a, &b| keys.get(a).cmp(keys.get(b)));
+    order
+}
+
+/// earliest starts after edge finding: when a task i cannot end before a
+/// set of tasks ends, it starts after they all complete, fails when some
+/// tasks cannot complete before the last of their completion times
+fn edge_finding(tasks: &[Task]) -> Result<Vec<int>, Failure> {
+    let mut ests: Vec<int> = tasks.iter().map(|t| t.est).collect();
+    if tasks.is_empty() { return Ok(ests); }
+    let (rank, order) = leaves(tasks);
+    let mut tree = ThetaLambda::new(tasks.len());
+    for (i, task) in tasks.iter().enumerate() {
+        tree.insert(*rank.get(i), task);
+    }
+    let by_lct = sorted_by(tasks, |t| -t.lct);
+    let mut j = *by_lct.get(0);
+    for k in range(1, by_lct.len() + 1) {
+        if tree.ect() > tasks[j].lct { return Err(Failure); }
+        tree.gray(*rank.get(j), &tasks[j]);
+        if k == by_lct.len() { break; }
+        j = *by_lct.get(k);
+        while tree.gray_ect() > tasks[j].lct {
+            let leaf = match tree.responsible() {
+                Some(leaf) => leaf,
+                // Theta alone is overloaded, found by the next check
+                None => break
+            };
+            let i = *order.get(leaf);
+            *ests.get_mut(i) = max(*ests.get(i), tree.ect());
+            tree.remove(leaf);
+        }
+    }
+    Ok(ests)
+}
+
+/// earliest starts after detectable precedences: a task j has to precede
+/// a task i when i cannot complete before j has to start
+fn detectable_precedences(tasks: &[Task]) -> Vec<int> {
+    let mut ests: Vec<int> = tasks.iter().map(|t| t.est).collect();
+    let (rank, _) = leaves(tasks);
+    let mut tree = ThetaLambda::new(tasks.len());
+    let mut inserted = Vec::from_elem(tasks.len(), false);
+    let by_lst = sorted_by(tasks, |t| t.lst());
+    let mut q = 0;
+    for &i in sorted_by(tasks, |t| t.ect()).iter() {
+        while q < tasks.len() && tasks[i].ect() > tasks[*by_lst.get(q)].lst() {
+            let j = *by_lst.get(q);
+            tree.insert(*rank.get(j), &tasks[j]);
+            *inserted.get_mut(j) = true;
+            q += 1;
+        }
+        // completion of the tasks preceding i, without i itself
+        if *inserted.get(i) { tree.remove(*rank.get(i)); }
+        *ests.get_mut(i) = max(*ests.get(i), tree.ect());
+        if *inserted.get(i) { tree.insert(*rank.get(i), &tasks[i]); }
+    }
+    ests
+}
+
+/// latest completion times after not-last: when the tasks that may start
+/// before i completes cannot all complete before i has to start, i ends
+/// before the latest start of one of them
+fn not_last(tasks: &[Task]) -> Vec<int> {
+    let mut lcts: Vec<int> = tasks.iter().map(|t| t.lct).collect();
+    let (rank, _) = leaves(tasks);
+    let mut tree = ThetaLambda::new(tasks.len());
+    let by_lst = sorted_by(tasks, |t| t.lst());
+    let mut q = 0;
+    for &i in sorted_by(tasks, |t| t.lct).iter() {
+        while q < tasks.len() && tasks[i].lct > tasks[*by_lst.get(q)].lst() {
+            let j = *by_lst.get(q);
+            tree.insert(*rank.get(j), &tasks[j]);
+            q += 1;
+        }
+        // i is always in the tree, as its own lst is less than its lct
+        tree.remove(*rank.get(i));
+        if tree.ect() > tasks[i].lst() {
+            // the latest start among the others
+            let j = if *by_lst.get(q - 1) != i { *by_lst.get(q - 1) } else { *by_lst.get(q - 2) };
+            *lcts.get_mut(i) = min(*lcts.get(i), tasks[j].lst());
+        }
+        tree.insert(*rank.get(i), &tasks[i]);
+    }
+    lcts
+}
+
+/// the values of a mirrored filtering, for the original tasks
+fn mirrored(bounds: Vec<int>) -> Vec<int> {
+    bounds.move_iter().map(|b| -b).collect()
+}
+
+struct DisjunctiveProp : Prop {
+    durations: Vec<int>
+}
+
+impl DisjunctiveProp {
+    fn new(model: Rc<Mod>, starts: &[Rc<FDVar>], durations: &[int]) {
+        assert!(starts.len() == durations.len(), "one duration per task");
+        let id = model.propagators.borrow().len();
+        let this = DisjunctiveProp {
+            model: model.downgrade(),
+            id: id,
+            vars: starts.to_vec(),
+            durations: durations.to_vec()
+        };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    /// the tasks with a positive duration
+    fn tasks(&self) -> Vec<Task> {
+        let mut tasks = Vec::new();
+        for (i, x) in self.vars.iter().enumerate() {
+            let duration = *self.durations.get(i);
+            if duration > 0 {
+                // one task at a time, as with a capacity of 1
+                tasks.push(Task::new(i, &**x, duration, 1));
+            }
+        }
+        tasks
+    }
+}
+
+impl Propagator for DisjunctiveProp {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        let mut events = Vec::new();
+        for x in self.vars.iter() {
+            events.push((x.id, Min));
+            events.push((x.id, Max));
+        }
+        events
+    }
+
+    fn priority(&self) -> uint {
+        2
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let tasks = self.tasks();
+        let backwards: Vec<Task> = tasks.iter().map(|t| t.mirror()).collect();
+        let (tasks, backwards) = (tasks.as_slice(), backwards.as_slice());
+        // not-first is not-last with time going backwards, and so on
+        let ests = vec![try!(edge_finding(tasks)),
+                        detectable_precedences(tasks),
+                        mirrored(not_last(backwards))];
+        let lcts = vec![mirrored(try!(edge_finding(backwards))),
+                        mirrored(detectable_precedences(backwards)),
+                        not_last(tasks)];
+        let mut woken = Vec::new();
+        for (k, task) in tasks.iter().enumerate() {
+            let est = ests.iter().fold(task.est, |est, bounds| max(est, *bounds.get(k)));
+            let lct = lcts.iter().fold(task.lct, |lct, bounds| min(lct, *bounds.get(k)));
+            let x = self.vars.get(task.index);
+            woken.push_all(try!(x.set_min(est)).as_slice());
+            woken.push_all(try!(x.set_max(lct - task.duration)).as_slice());
+        }
+        Ok(woken)
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,66 @@
+use super::super::{Model, Var, FDVar, Disjunctive};
+use super::super::scheduling::task;
+use super::{edge_finding, detectable_precedences, not_last};
+
+use std::rc::Rc;
+
+#[test]
+fn edge_finding_on_tasks() {
+    let tasks = [task(0, 5, 2, 1), task(0, 5, 2, 1), task(0, 12, 2, 1)];
+    assert_eq!(edge_finding(tasks.as_slice()), Ok(vec![0, 0, 4]));
+    let tasks = [task(0, 5, 2, 1), task(0, 5, 2, 1), task(1, 5, 2, 1)];
+    assert!(edge_finding(tasks.as_slice()).is_err());
+}
+
+#[test]
+fn detectable_precedences_on_tasks() {
+    let tasks = [task(0, 4, 3, 1), task(0, 12, 2, 1)];
+    assert_eq!(detectable_precedences(tasks.as_slice()), vec![0, 3]);
+}
+
+#[test]
+fn not_last_on_tasks() {
+    let tasks = [task(0, 10, 3, 1), task(2, 12, 4, 1), task(2, 12, 4, 1)];
+    assert_eq!(not_last(tasks.as_slice()), vec![8, 12, 12]);
+}
+
+#[test]
+fn pushes_both_bounds() {
+    let m = Model::new();
+    let a = Var::new(m.clone(), 0, 1, "a");
+    let b = Var::new(m.clone(), 0, 10, "b");
+    Disjunctive::new(m.clone(), &[a, b.clone()], &[3, 2]);
+    assert_eq!((b.min(), b.max()), (3, 10));
+    let m = Model::new();
+    let a = Var::new(m.clone(), 8, 9, "a");
+    let b = Var::new(m.clone(), 0, 10, "b");
+    Disjunctive::new(m.clone(), &[a, b.clone()], &[3, 2]);
+    assert_eq!((b.min(), b.max()), (0, 7));
+}
+
+#[test]
+fn detects_overload() {
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 0, 3, format!("s{}", i).as_slice()))
+        .collect();
+    Disjunctive::new(m.clone(), starts.as_slice(), &[2, 2, 2]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn schedules_tasks() {
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 4)
+        .map(|i| Var::new(m.clone(), 0, 6, format!("s{}", i).as_slice()))
+        .collect();
+    Disjunctive::new(m.clone(), starts.as_slice(), &[2, 2, 2, 2]);
+    assert_eq!(m.count_solutions(starts.clone()), 24);
+    // empty tasks take no time on the machine
+    let m = Model::new();
+    let starts: Vec<Rc<FDVar>> = range(0, 3)
+        .map(|i| Var::new(m.clone(), 0, 2, format!("s{}", i).as_slice()))
+        .collect();
+    Disjunctive::new(m.clone(), starts.as_slice(), &[1, 2, 0]);
+    assert_eq!(m.count_solutions(starts.clone()), 4 * 3);
+}
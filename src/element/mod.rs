@@ -0,0 +1,175 @@
+use super::{Event, Dom, Prop, Mod, FDVar, Propagator, Failure};
+
+use std::rc::{Rc, Weak};
+
+/// V = A[I] for an array A of constants
+pub struct Element;
+
+impl Element {
+    pub fn new(model: Rc<Mod>, index: Rc<FDVar>, array: &[int], value: Rc<FDVar>) {
+        ElementC::new(model, index, array.to_vec(), value);
+    }
+}
+
+/// V = X[I] for an array X of variables
+pub struct VarElement;
+
+impl VarElement {
+    pub fn new(model: Rc<Mod>, index: Rc<FDVar>, array: &[Rc<FDVar>], value: Rc<FDVar>) {
+        ElementX::new(model, index, array, value);
+    }
+}
+
+/// domain consistent for both the index and the value
+struct ElementC : Prop {
+    array: Vec<int>
+}
+
+impl ElementC {
+    fn new(model: Rc<Mod>, index: Rc<FDVar>, array: Vec<int>, value: Rc<FDVar>) {
+        let id = model.propagators.borrow().len();
+        let this = ElementC { model: model.downgrade(), id: id, vars: vec![index, value], array: array };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    fn index(&self) -> Rc<FDVar> {
+        self.vars.get(0).clone()
+    }
+
+    fn value(&self) -> Rc<FDVar> {
+        self.vars.get(1).clone()
+    }
+}
+
+impl Propagator for ElementC {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        vec![(self.index().id, Dom), (self.value().id, Dom)]
+    }
+
+    fn priority(&self) -> uint {
+        1
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let (index, value) = (self.index(), self.value());
+        let n = self.array.len() as int;
+        let supported: Vec<int> = index.values()
+            .filter(|&i| i >= 0 && i < n && value.contains(*self.array.get(i as uint)))
+            .collect();
+        let mut woken = try!(index.restrict_to(supported.as_slice()));
+        // every value left in the index has a support in the value
+        let values: Vec<int> = index.values().map(|i| *self.array.get(i as uint)).collect();
+        woken.push_all(try!(value.restrict_to(values.as_slice())).as_slice());
+        if index.is_fixed() {
+            self.entail();
+        }
+        Ok(woken)
+    }
+}
+
+/// domain consistent for the index and the value, the variables of the
+/// array are only pruned once the index is fixed
+struct ElementX : Prop {
+    /// number of variables in the array, followed by the index and value
+    /// in vars
+    n: uint
+}
+
+impl ElementX {
+    fn new(model: Rc<Mod>, index: Rc<FDVar>, array: &[Rc<FDVar>], value: Rc<FDVar>) {
+        let id = model.propagators.borrow().len();
+        let mut vars = array.to_vec();
+        vars.push(index);
+        vars.push(value);
+        let this = ElementX { model: model.downgrade(), id: id, vars: vars, n: array.len() };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    fn index(&self) -> Rc<FDVar> {
+        self.vars.get(self.n).clone()
+    }
+
+    fn value(&self) -> Rc<FDVar> {
+        self.vars.get(self.n + 1).clone()
+    }
+
+    fn array(&self, i: int) -> Rc<FDVar> {
+        self.vars.get(i as uint).clone()
+    }
+}
+
+impl Propagator for ElementX {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        self.vars.iter().map(|x| (x.id, Dom)).collect()
+    }
+
+    fn priority(&self) -> uint {
+        1
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let (index, value) = (self.index(), self.value());
+        let n = self.n as int;
+        let supported: Vec<int> = index.values()
+            .filter(|&i| i >= 0 && i < n && self.array(i).values().any(|v| value.contains(v)))
+            .collect();
+        let mut woken = try!(index.restrict_to(supported.as_slice()));
+        let mut intervals = Vec::new();
+        for i in index.values() {
+            intervals.extend(self.array(i).intervals());
+        }
+        woken.push_all(try!(value.intersect_with(union(intervals).as_slice())).as_slice());
+        match index.value() {
+            Some(i) => {
+                let x = self.array(i);
+                let fixed: Vec<(int, int)> = value.intervals().collect();
+                woken.push_all(try!(x.intersect_with(fixed.as_slice())).as_slice());
+                let fixed: Vec<(int, int)> = x.intervals().collect();
+                woken.push_all(try!(value.intersect_with(fixed.as_slice())).as_slice());
+            },
+            None => ()
+        }
+        Ok(woken)
+    }
+}
+
+/// sorted and disjoint intervals covering the same values as intervals
+fn union(intervals: Vec<(int, int)>) -> Vec<(int, int)> {
+    let mut intervals = intervals;
+    intervals.sort();
+    let mut merged: Vec<(int, int)> = Vec::new();
+    for &(lo, hi) in intervals.iter() {
+        let extends = match merged.last() {
+            Some(&(_, end)) => lo <= end + 1,
+            None => false
+        };
+        if extends {
+            let (start, end) = merged.pop().unwrap();
+            merged.push((start, if hi > end { hi } else { end }));
+        } else {
+            merged.push((lo, hi));
+        }
+    }
+    merged
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,81 @@
+use super::super::{Model, Var, LeXC, GeXC, NeqXC, InXS, Element, VarElement};
+use super::union;
+
+#[test]
+fn element_restricts_index_and_value() {
+    let m = Model::new();
+    let i = Var::new(m.clone(), -3, 20, "i");
+    let v = Var::new(m.clone(), 0, 100, "v");
+    Element::new(m.clone(), i.clone(), &[10, 40, 20, 40, 30, 200], v.clone());
+    assert_eq!((i.min(), i.max(), i.size()), (0, 4, 5));
+    assert_eq!(v.intervals().collect::<Vec<(int, int)>>(),
+               vec![(10, 10), (20, 20), (30, 30), (40, 40)]);
+    NeqXC::new(m.clone(), v.clone(), 40);
+    assert_eq!(i.intervals().collect::<Vec<(int, int)>>(), vec![(0, 0), (2, 2), (4, 4)]);
+    LeXC::new(m.clone(), i.clone(), 2);
+    assert_eq!((v.min(), v.max(), v.size()), (10, 20, 2));
+}
+
+#[test]
+fn element_fixes_value_with_index() {
+    let m = Model::new();
+    let i = Var::new(m.clone(), 0, 3, "i");
+    let v = Var::new(m.clone(), -10, 10, "v");
+    Element::new(m.clone(), i.clone(), &[5, -5, 7, 0], v.clone());
+    GeXC::new(m.clone(), i.clone(), 3);
+    assert_eq!(v.value(), Some(0));
+    assert_eq!(m.count_solutions(vec![i.clone(), v.clone()]), 1);
+}
+
+#[test]
+fn element_fails_without_support() {
+    let m = Model::new();
+    let i = Var::new(m.clone(), 0, 3, "i");
+    let v = Var::new(m.clone(), 0, 10, "v");
+    Element::new(m.clone(), i.clone(), &[11, 12, 3, 4], v.clone());
+    assert!(!m.is_failed());
+    InXS::new(m.clone(), v.clone(), &[0, 1, 2, 5, 6]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn var_element_prunes_index_and_value() {
+    let m = Model::new();
+    let xs = vec![Var::new(m.clone(), 0, 5, "x0"),
+                  Var::new(m.clone(), 10, 15, "x1"),
+                  Var::new(m.clone(), 3, 12, "x2")];
+    let i = Var::new(m.clone(), -1, 5, "i");
+    let v = Var::new(m.clone(), 7, 30, "v");
+    VarElement::new(m.clone(), i.clone(), xs.as_slice(), v.clone());
+    assert_eq!((i.min(), i.max()), (1, 2));
+    assert_eq!((v.min(), v.max()), (7, 15));
+    LeXC::new(m.clone(), xs.get(2).clone(), 8);
+    assert_eq!((v.min(), v.max()), (7, 15));
+    assert!(!v.contains(9));
+    LeXC::new(m.clone(), v.clone(), 9);
+    assert_eq!(i.value(), Some(2));
+    assert_eq!((xs.get(2).min(), xs.get(2).max()), (7, 8));
+    assert_eq!((xs.get(0).max(), xs.get(1).min()), (5, 10));
+}
+
+#[test]
+fn var_element_links_chosen_variable() {
+    let m = Model::new();
+    let xs = vec![Var::new(m.clone(), 0, 9, "x0"), Var::new(m.clone(), 0, 9, "x1")];
+    let i = Var::new(m.clone(), 0, 1, "i");
+    let v = Var::new(m.clone(), 0, 9, "v");
+    VarElement::new(m.clone(), i.clone(), xs.as_slice(), v.clone());
+    GeXC::new(m.clone(), i.clone(), 1);
+    NeqXC::new(m.clone(), v.clone(), 4);
+    GeXC::new(m.clone(), xs.get(1).clone(), 3);
+    assert!(!xs.get(1).contains(4) && xs.get(0).contains(4));
+    assert_eq!(v.min(), 3);
+    assert_eq!(m.count_solutions(vec![xs.get(1).clone(), v.clone()]), 6);
+}
+
+#[test]
+fn union_merges_intervals() {
+    assert_eq!(union(vec![(5, 7), (0, 2), (3, 3), (6, 10), (13, 14)]),
+               vec![(0, 3), (5, 10), (13, 14)]);
+    assert!(union(vec![]).is_empty());
+}
@@ -1,4 +1,4 @@
-use super::{Event, Ins, Prop, Mod, FDVar, Propagator, LeXY, GeXY, LeXYC, GeXYC, LeXC, GeXC};
+use super::{Event, Ins, Dom, Prop, Mod, FDVar, Propagator, Failure, LeXC, GeXC};
 
 use std::rc::{Rc, Weak};
 
@@ -7,9 +7,7 @@ pub struct EqXY;
 
 impl EqXY {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, y: Rc<FDVar>) {
-        // TODO merge or at least intersect domains
-        LeXY::new(model.clone(), x.clone(), y.clone());
-        GeXY::new(model, x, y);
+        EqXYCxy::new(model, x, y, 0);
     }
 }
 
@@ -18,9 +16,7 @@ pub struct EqXYC;
 
 impl EqXYC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
-        // TODO merge or at least intersect domains
-        LeXYC::new(model.clone(), x.clone(), y.clone(), c);
-        GeXYC::new(model, x, y, c);
+        EqXYCxy::new(model, x, y, c);
     }
 }
 
@@ -56,10 +52,75 @@ impl NeqXYC {
 /// X != C
 pub struct NeqXC;
 
-#[allow(unused_variable)]
 impl NeqXC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, c: int) {
-        x.remove(c);
+        model.post(x.remove(c));
+    }
+}
+
+/// intervals shifted by c
+fn shifted(intervals: Vec<(int, int)>, c: int) -> Vec<(int, int)> {
+    intervals.move_iter().map(|(lo, hi)| (lo + c, hi + c)).collect()
+}
+
+/// x = y + c, removes the values without support in the other domain
+pub fn filter_eq(x: &FDVar, y: &FDVar, c: int) -> Result<Vec<uint>, Failure> {
+    let mut woken = try!(x.intersect_with(shifted(y.intervals().collect(), c).as_slice()));
+    // x is now included in y + c, so a single pass reaches the fixpoint
+    let more = try!(y.intersect_with(shifted(x.intervals().collect(), -c).as_slice()));
+    woken.push_all(more.as_slice());
+    Ok(woken)
+}
+
+/// x != y + c, removes the value of the other variable once one is fixed
+pub fn filter_neq(x: &FDVar, y: &FDVar, c: int) -> Result<Vec<uint>, Failure> {
+    if x.is_instanciated() {
+        y.remove(x.min() - c)
+    } else if y.is_instanciated() {
+        x.remove(y.min() + c)
+    } else {
+        Ok(vec![])
+    }
+}
+
+/// x = y + c, removing values without support in the other domain,
+/// holes included
+struct EqXYCxy : Prop {
+    c: int
+}
+
+impl EqXYCxy {
+    fn new(model: Rc<Mod>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        let id = model.propagators.borrow().len();
+        let this = EqXYCxy { model: model.downgrade(), id: id, vars: vec![x, y], c: c};
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    fn x(&self) -> Rc<FDVar> {
+        self.vars.get(0).clone()
+    }
+
+    fn y(&self) -> Rc<FDVar> {
+        self.vars.get(1).clone()
+    }
+}
+
+impl Propagator for EqXYCxy {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        vec![(self.y().id, Dom), (self.x().id, Dom)]
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        filter_eq(&*self.x(), &*self.y(), self.c)
     }
 }
 
@@ -96,17 +157,11 @@ impl Propagator for NeqXYCxy {
         vec![(self.y().id, Ins), (self.x().id, Ins)]
     }
 
-    fn propagate(&self) -> Vec<uint> {
-        if self.x().is_instanciated() {
-            self.unregister();
-            self.y().remove(self.x().min() - self.c)
-        }
-        else if self.y().is_instanciated() {
-            self.unregister();
-            self.x().remove(self.y().min() + self.c)
-        } else {
-            vec![]
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        if self.x().is_instanciated() || self.y().is_instanciated() {
+            self.entail();
         }
+        filter_neq(&*self.x(), &*self.y(), self.c)
     }
 }
 
@@ -1,5 +1,5 @@
-use super::super::{Model, Var, EqXY, NeqXY, EqXYC, NeqXYC, EqXC, NeqXC};
-use super::{NeqXYCxy};
+use super::super::{Model, Var, LeXC, EqXY, NeqXY, EqXYC, NeqXYC, EqXC, NeqXC};
+use super::{NeqXYCxy, EqXYCxy};
 
 #[test]
 fn neqxycxy_does_propagate() {
@@ -69,3 +69,56 @@ fn neqxc_does_propagate() {
     NeqXC::new(m.clone(), x.clone(), 8);
     assert_eq!((x.min(), x.max()), (11, 255));
 }
+
+#[test]
+fn neqxy_fails_on_equal_instances() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 3, 3, "x");
+    let y = Var::new(m.clone(), 3, 3, "y");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    assert!(m.is_failed());
+}
+
+#[test]
+fn eqxy_shares_holes() {
+    let m = Model::new();
+    let x = Var::new_intervals(m.clone(), 0, 100, "x");
+    let y = Var::new_sparse(m.clone(), 0, 100, "y");
+    NeqXC::new(m.clone(), x.clone(), 10);
+    NeqXC::new(m.clone(), y.clone(), 20);
+    EqXY::new(m.clone(), x.clone(), y.clone());
+    assert!(!x.contains(20) && !y.contains(10));
+    NeqXC::new(m.clone(), y.clone(), 50);
+    assert!(!x.contains(50));
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(),
+               y.intervals().collect::<Vec<(int, int)>>());
+    assert_eq!(x.size(), 98);
+}
+
+#[test]
+fn eqxycxy_shifts_domains() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 30, "x");
+    let y = Var::new(m.clone(), 0, 30, "y");
+    EqXYCxy::new(m.clone(), x.clone(), y.clone(), 5);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (5, 30, 0, 25));
+    NeqXC::new(m.clone(), x.clone(), 12);
+    NeqXC::new(m.clone(), y.clone(), 3);
+    assert_eq!(y.intervals().collect::<Vec<(int, int)>>(), vec![(0, 2), (4, 6), (8, 25)]);
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), vec![(5, 7), (9, 11), (13, 30)]);
+    LeXC::new(m.clone(), y.clone(), 3);
+    assert_eq!((x.min(), x.max()), (5, 7));
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone()]), 3);
+}
+
+#[test]
+fn eqxyc_fails_on_disjoint_domains() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    NeqXC::new(m.clone(), x.clone(), 5);
+    EqXYC::new(m.clone(), x.clone(), y.clone(), 5);
+    assert!(!m.is_failed());
+    LeXC::new(m.clone(), y.clone(), 0);
+    assert!(m.is_failed());
+}
@@ -0,0 +1,239 @@
+use super::{Mod, FDVar};
+use super::search::{Decision, Assign, AtMost};
+
+use std::cmp::max;
+use std::rand::{Rng, SeedableRng, XorShiftRng};
+use std::rc::Rc;
+
+/// Variable ordering for labelling
+pub trait VarSelector {
+    /// index in vars of the next variable to branch on, vars are the
+    /// labelled variables not yet instanciated, in input order, and there
+    /// is at least one of them
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint;
+}
+
+/// variables in the order they were given
+pub struct InputOrder;
+
+/// smallest domain first
+pub struct FirstFail;
+
+/// smallest minimum first
+pub struct SmallestMin;
+
+/// largest minimum first
+pub struct LargestMin;
+
+/// smallest maximum first
+pub struct SmallestMax;
+
+/// largest maximum first
+pub struct LargestMax;
+
+/// variable with the most propagators waiting on it first
+pub struct MostConstrained;
+
+/// smallest ratio of domain size to weighted degree first, the weight of a
+/// propagator grows each time it fails
+pub struct DomWDeg;
+
+/// index of the first variable with the smallest key
+fn smallest_by(vars: &[Rc<FDVar>], key: |&FDVar| -> int) -> uint {
+    let mut best = 0;
+    let mut best_key = key(&*vars[0]);
+    for (i, x) in vars.iter().enumerate().skip(1) {
+        let k = key(&**x);
+        if k < best_key {
+            best = i;
+            best_key = k;
+        }
+    }
+    best
+}
+
+impl VarSelector for InputOrder {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        0
+    }
+}
+
+impl VarSelector for FirstFail {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| x.size() as int)
+    }
+}
+
+impl VarSelector for SmallestMin {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| x.min())
+    }
+}
+
+impl VarSelector for LargestMin {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| - x.min())
+    }
+}
+
+impl VarSelector for SmallestMax {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| x.max())
+    }
+}
+
+impl VarSelector for LargestMax {
+    #[allow(unused_variable)]
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| - x.max())
+    }
+}
+
+impl VarSelector for MostConstrained {
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        smallest_by(vars, |x| - (model.degree(x.id) as int))
+    }
+}
+
+impl VarSelector for DomWDeg {
+    fn select(&mut self, model: &Mod, vars: &[Rc<FDVar>]) -> uint {
+        // compare size / wdeg as products to stay with integers, a weighted
+        // degree of 0 is taken as 1
+        let ratio = |x: &FDVar| (x.size(), max(model.weighted_degree(x.id), 1));
+        let mut best = 0;
+        let (mut best_size, mut best_wdeg) = ratio(&*vars[0]);
+        for (i, x) in vars.iter().enumerate().skip(1) {
+            let (size, wdeg) = ratio(&**x);
+            if size * best_wdeg < best_size * wdeg {
+                best = i;
+                best_size = size;
+                best_wdeg = wdeg;
+            }
+        }
+        best
+    }
+}
+
+/// Branching on the selected variable
+pub trait Brancher {
+    /// decision tried first, its alternative being tried on backtrack, both
+    /// have to remove values from the domain of x, which is not instanciated
+    fn branch(&mut self, x: &Rc<FDVar>) -> Decision;
+}
+
+/// Value ordering
+pub trait ValueSelector {
+    /// a value in the domain of x
+    fn select(&mut self, x: &FDVar) -> int;
+}
+
+/// x = v / x != v with v given by a ValueSelector
+pub struct AssignValue<V> {
+    selector: V
+}
+
+/// smallest value first
+pub struct MinValue;
+
+/// largest value first
+pub struct MaxValue;
+
+/// middle value of the domain first, the lower one for even sizes
+pub struct MedianValue;
+
+/// random value first, reproducible from its seed
+pub struct RandomValue {
+    rng: XorShiftRng
+}
+
+/// x =< mid / x > mid with mid the middle of the bounds
+pub struct Bisect;
+
+/// x in the first interval of the domain / x after it, bisects when the
+/// domain has no holes
+pub struct SplitIntervals;
+
+impl<V: ValueSelector> AssignValue<V> {
+    pub fn new(selector: V) -> AssignValue<V> {
+        AssignValue { selector: selector }
+    }
+}
+
+impl<V: ValueSelector> Brancher for AssignValue<V> {
+    fn branch(&mut self, x: &Rc<FDVar>) -> Decision {
+        Assign(x.clone(), self.selector.select(&**x))
+    }
+}
+
+/// value of rank n, from 0, in the domain of x
+fn nth_value(x: &FDVar, n: uint) -> int {
+    let mut n = n;
+    for (min, max) in x.intervals() {
+        let size = (max - min + 1) as uint;
+        if n < size {
+            return min + n as int;
+        }
+        n -= size;
+    }
+    fail!("not enough values in {}", x)
+}
+
+fn middle(x: &FDVar) -> int {
+    x.min() + (x.max() - x.min()) / 2
+}
+
+impl ValueSelector for MinValue {
+    fn select(&mut self, x: &FDVar) -> int {
+        x.min()
+    }
+}
+
+impl ValueSelector for MaxValue {
+    fn select(&mut self, x: &FDVar) -> int {
+        x.max()
+    }
+}
+
+impl ValueSelector for MedianValue {
+    fn select(&mut self, x: &FDVar) -> int {
+        nth_value(x, (x.size() - 1) / 2)
+    }
+}
+
+impl RandomValue {
+    pub fn new(seed: u32) -> RandomValue {
+        // the seed of a XorShiftRng cannot be all zeros
+        RandomValue { rng: SeedableRng::from_seed([seed, 0x193a6754, 0xa8a7d469, 0x97830e05]) }
+    }
+}
+
+impl ValueSelector for RandomValue {
+    fn select(&mut self, x: &FDVar) -> int {
+        let n = self.rng.gen_range(0, x.size());
+        nth_value(x, n)
+    }
+}
+
+impl Brancher for Bisect {
+    fn branch(&mut self, x: &Rc<FDVar>) -> Decision {
+        AtMost(x.clone(), middle(&**x))
+    }
+}
+
+impl Brancher for SplitIntervals {
+    fn branch(&mut self, x: &Rc<FDVar>) -> Decision {
+        let mut intervals = x.intervals();
+        match (intervals.next(), intervals.next()) {
+            (Some((_, max)), Some(_)) => AtMost(x.clone(), max),
+            _ => AtMost(x.clone(), middle(&**x))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,160 @@
+use super::super::{Model, Var, FDVar, NeqXC, NeqXY, LtXY, Search, Assign, AtMost,
+    Disjunctive};
+use super::{VarSelector, InputOrder, FirstFail, SmallestMin, LargestMin, SmallestMax,
+    LargestMax, MostConstrained, DomWDeg};
+use super::{ValueSelector, Brancher, AssignValue, MinValue, MaxValue, MedianValue, RandomValue,
+    Bisect, SplitIntervals};
+
+use std::rc::Rc;
+
+#[test]
+fn selects_on_bounds_and_size() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), -5, 3, "y");
+    let z = Var::new(m.clone(), 2, 12, "z");
+    NeqXC::new(m.clone(), z.clone(), 5);
+    NeqXC::new(m.clone(), z.clone(), 6);
+    NeqXC::new(m.clone(), z.clone(), 7);
+    let vars = vec![x.clone(), y.clone(), z.clone()];
+    let vars = vars.as_slice();
+    assert_eq!(InputOrder.select(&*m, vars), 0);
+    assert_eq!(FirstFail.select(&*m, vars), 2);
+    assert_eq!(SmallestMin.select(&*m, vars), 1);
+    assert_eq!(LargestMin.select(&*m, vars), 2);
+    assert_eq!(SmallestMax.select(&*m, vars), 1);
+    assert_eq!(LargestMax.select(&*m, vars), 2);
+}
+
+#[test]
+fn selects_most_constrained() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let z = Var::new(m.clone(), 0, 10, "z");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    NeqXY::new(m.clone(), y.clone(), z.clone());
+    assert_eq!(MostConstrained.select(&*m, vec![x, y, z].as_slice()), 1);
+}
+
+#[test]
+fn degree_counts_propagators_once() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let w = Var::new(m.clone(), 0, 10, "w");
+    // waiting on both the min and the max of x
+    Disjunctive::new(m.clone(), &[x.clone(), w.clone()], &[1, 1]);
+    NeqXY::new(m.clone(), y.clone(), w.clone());
+    NeqXY::new(m.clone(), y.clone(), w.clone());
+    assert_eq!((m.degree(x.id), m.degree(y.id)), (1, 2));
+    assert_eq!(MostConstrained.select(&*m, vec![x, y].as_slice()), 1);
+}
+
+#[test]
+fn dom_wdeg_prefers_failing_variables() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let z = Var::new(m.clone(), 0, 10, "z");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    LtXY::new(m.clone(), y.clone(), z.clone());
+    let vars: Vec<Rc<FDVar>> = vec![x.clone(), y.clone(), z.clone()];
+    // y has two propagators and the smallest domain
+    assert_eq!(DomWDeg.select(&*m, vars.as_slice()), 1);
+    // the only propagator on z, waiting for its max, failed a lot
+    *m.failures.borrow_mut().get_mut(1) += 9;
+    assert_eq!(DomWDeg.select(&*m, vars.as_slice()), 2);
+}
+
+#[test]
+fn searches_with_first_fail() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 1, "y");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    let mut search = Search::new(m.clone(), vec![x.clone(), y.clone()])
+        .select_var(box FirstFail as Box<VarSelector>);
+    let sol = search.solve().unwrap();
+    assert_eq!((sol.get(&"x".to_string()), sol.get(&"y".to_string())), (&1, &0));
+}
+
+#[test]
+fn selects_values() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    NeqXC::new(m.clone(), x.clone(), 4);
+    NeqXC::new(m.clone(), x.clone(), 5);
+    NeqXC::new(m.clone(), x.clone(), 6);
+    // 0 1 2 3 7 8 9 10
+    assert_eq!(MinValue.select(&*x), 0);
+    assert_eq!(MaxValue.select(&*x), 10);
+    assert_eq!(MedianValue.select(&*x), 3);
+    let mut random = RandomValue::new(42);
+    for _ in range(0, 20) {
+        let v = random.select(&*x);
+        assert!(v >= 0 && v <= 10 && (v < 4 || v > 6));
+    }
+}
+
+#[test]
+fn random_values_are_reproducible() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 1000, "x");
+    let mut r1 = RandomValue::new(7);
+    let mut r2 = RandomValue::new(7);
+    for _ in range(0, 20) {
+        assert_eq!(r1.select(&*x), r2.select(&*x));
+    }
+}
+
+#[test]
+fn branches() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), -3, 10, "x");
+    match AssignValue::new(MaxValue).branch(&x) {
+        Assign(_, v) => assert_eq!(v, 10),
+        _ => fail!("x = max expected")
+    }
+    match Bisect.branch(&x) {
+        AtMost(_, v) => assert_eq!(v, 3),
+        _ => fail!("x =< mid expected")
+    }
+    match SplitIntervals.branch(&x) {
+        AtMost(_, v) => assert_eq!(v, 3),
+        _ => fail!("x =< mid expected")
+    }
+    NeqXC::new(m.clone(), x.clone(), 0);
+    match SplitIntervals.branch(&x) {
+        AtMost(_, v) => assert_eq!(v, -1),
+        _ => fail!("x =< -1 expected")
+    }
+}
+
+#[test]
+fn searches_with_bisection() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    let count = Search::new(m.clone(), vec![x.clone(), y.clone()])
+        .branch_with(box Bisect as Box<Brancher>)
+        .count_solutions();
+    assert_eq!(count, 55);
+    let count = Search::new(m.clone(), vec![x.clone(), y.clone()])
+        .branch_with(box SplitIntervals as Box<Brancher>)
+        .count_solutions();
+    assert_eq!(count, 55);
+}
+
+#[test]
+fn searches_with_max_value() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    let mut search = Search::new(m.clone(), vec![x.clone(), y.clone()])
+        .branch_with(box AssignValue::new(MaxValue) as Box<Brancher>);
+    let sol = search.solve().unwrap();
+    assert_eq!((sol.get(&"x".to_string()), sol.get(&"y".to_string())), (&9, &10));
+}
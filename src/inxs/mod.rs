@@ -0,0 +1,38 @@
+use super::{Mod, FDVar};
+
+use std::rc::Rc;
+
+/// X in S
+pub struct InXS;
+
+impl InXS {
+    pub fn new(model: Rc<Mod>, x: Rc<FDVar>, values: &[int]) {
+        model.post(x.restrict_to(values));
+    }
+}
+
+/// X not in S
+pub struct NotInXS;
+
+impl NotInXS {
+    pub fn new(model: Rc<Mod>, x: Rc<FDVar>, values: &[int]) {
+        let mut sorted = values.to_vec();
+        sorted.sort();
+        sorted.dedup();
+        // complement of the values within the bounds of x
+        let mut intervals = Vec::new();
+        let mut from = x.min();
+        for &v in sorted.iter() {
+            if v > x.max() { break; }
+            if v >= from {
+                if v > from { intervals.push((from, v - 1)); }
+                from = v + 1;
+            }
+        }
+        if from <= x.max() { intervals.push((from, x.max())); }
+        model.post(x.intersect_with(intervals.as_slice()));
+    }
+}
+
+#[cfg(test)]
+mod tests;
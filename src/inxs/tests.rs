@@ -0,0 +1,46 @@
+use super::super::{Model, Var, InXS, NotInXS};
+
+#[test]
+fn inxs_keeps_only_values() {
+    let m = Model::new();
+    let x = Var::new_intervals(m.clone(), 0, 20, "x");
+    InXS::new(m.clone(), x.clone(), &[9, 1, 3, 5, 6, 7, 8, 42]);
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), vec![(1, 1), (3, 3), (5, 9)]);
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn inxs_fails_without_common_value() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 20, "x");
+    InXS::new(m.clone(), x.clone(), &[-3, 21, 30]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn notinxs_removes_values() {
+    let m = Model::new();
+    let x = Var::new_intervals(m.clone(), 0, 100, "x");
+    NotInXS::new(m.clone(), x.clone(), &[-1, 0, 1, 50, 52, 51, 100, 200]);
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), vec![(2, 49), (53, 99)]);
+}
+
+#[test]
+fn notinxs_fails_when_covering_domain() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 3, 5, "x");
+    NotInXS::new(m.clone(), x.clone(), &[5, 4, 3]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn inxs_is_undone_on_backtrack() {
+    let m = Model::new();
+    let x = Var::new_sparse(m.clone(), 0, 9, "x");
+    m.push_level();
+    InXS::new(m.clone(), x.clone(), &[2, 4, 6]);
+    assert_eq!(x.size(), 3);
+    m.pop_level();
+    assert_eq!(x.size(), 10);
+    assert_eq!(m.count_solutions(vec![x.clone()]), 10);
+}
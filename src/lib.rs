@@ -7,32 +7,146 @@
 #![feature(globs)]
 
 use std::fmt;
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::cmp::{min, max};
 use std::collections::hashmap::HashMap;
+use std::collections::RingBuf;
 use std::rc::{Rc, Weak};
+use std::vec::MoveItems;
 
 pub use ltxy::{LtXY, LtXYC, LeXY, LeXYC, GtXY, GtXYC, GeXY, GeXYC, LtXC, GtXC, LeXC, GeXC};
 pub use eqxy::{EqXY, EqXYC, EqXC, NeqXY, NeqXYC, NeqXC};
+pub use inxs::{InXS, NotInXS};
+pub use linear::{Sum, Linear, Relation, Equals, LessOrEqual, GreaterOrEqual, Differs};
+pub use alldiff::{AllDifferent, Consistency, ForwardChecking, BoundsConsistency, DomainConsistency};
+pub use element::{Element, VarElement};
+pub use table::{Table, NegativeTable};
+pub use cumulative::Cumulative;
+pub use disjunctive::Disjunctive;
+pub use reified::{ReifLtXYC, ReifLeXYC, ReifEqXYC, ReifNeqXYC, ReifLeXC, ReifGeXC, ReifEqXC,
+    ReifNeqXC};
+pub use search::{Search, Solution, Decision, Assign, Remove, AtMost, AtLeast};
+pub use heuristics::{VarSelector, InputOrder, FirstFail, SmallestMin, LargestMin, SmallestMax,
+    LargestMax, MostConstrained, DomWDeg};
+pub use heuristics::{ValueSelector, Brancher, AssignValue, MinValue, MaxValue, MedianValue,
+    RandomValue, Bisect, SplitIntervals};
 
 #[allow(dead_code)]
 pub struct Mod {
     vars: RefCell<Vec<Rc<FDVar>>>,
     propagators: RefCell<Vec<Rc<Box<Propagator>>>>,
-    waiting: RefCell<HashMap<(uint, Event), Vec<uint>>>
+    /// number of failures of each propagator, never undone
+    failures: RefCell<Vec<uint>>,
+    waiting: RefCell<HashMap<(uint, Event), Vec<uint>>>,
+    /// propagators scheduled for propagation, one queue per priority
+    queue: RefCell<Vec<RingBuf<uint>>>,
+    /// whether each propagator is in the queue
+    queued: RefCell<Vec<bool>>,
+    /// whether each propagator is entailed, hence never run
+    entailed: RefCell<Vec<bool>>,
+    failed: Cell<bool>,
+    trail: RefCell<Vec<Trailed>>,
+    levels: RefCell<Vec<uint>>,
+    stamp: Cell<uint>,
+    /// the Rc wrapping this model, so that searches can be started from it
+    me: RefCell<Option<Weak<Mod>>>
+}
+
+/// Changes recorded on the trail of a Mod, undone in reverse order when
+/// popping a level
+enum Trailed {
+    /// domain of a variable before its first modification in a level
+    SavedDomain(uint, VarDomain),
+    /// propagator removed from the waiting list of (var, event)
+    Unregistered(uint, Event, uint),
+    /// propagator marked as entailed
+    Entailed(uint),
+    /// propagator posted in the level, the last one
+    Added(uint),
+    /// words of a propagator before their first modification in a level
+    SavedWords(Rc<TrailedWords>, Vec<u64>),
+    /// the model was not failed
+    Failed
 }
 
 /// wrapping Mod in an Rc
 /// cannot use type Model = Rc<Mod> since that would forbid using impl Model
 pub struct Model;
 
+/// Failure of propagation, some domain became empty
+#[deriving(Show, Clone, PartialEq, Eq)]
+pub struct Failure;
+
 /// Generic Finite Domain trait
-trait Domain : fmt::Show {
+/// modifications return Ok(true) if the domain changed, Ok(false) if it did
+/// not and Err(Failure) if it would become empty, in which case it is left
+/// untouched
+trait Domain : fmt::Show + Clone {
     fn new(min: int, max: int) -> Self;
-    fn set_min(&self, min: int);
+    /// restores a copy taken earlier with clone
+    fn restore(&self, saved: &Self);
+    fn set_min(&self, min: int) -> Result<bool, Failure>;
     fn get_min(&self) -> int;
-    fn set_max(&self, max: int);
+    fn set_max(&self, max: int) -> Result<bool, Failure>;
     fn get_max(&self) -> int;
-    fn remove(&self, val: int);
+    fn remove(&self, val: int) -> Result<bool, Failure>;
+    /// number of values
+    fn size(&self) -> uint;
+    /// maximal intervals of consecutive values, in increasing order
+    fn intervals(&self) -> Vec<(int, int)>;
+    fn contains(&self, val: int) -> bool;
+    /// smallest value greater than val
+    fn next_value(&self, val: int) -> Option<int>;
+    /// largest value less than val
+    fn prev_value(&self, val: int) -> Option<int>;
+
+    /// removes the values from lo to hi included
+    fn remove_range(&self, lo: int, hi: int) -> Result<bool, Failure> {
+        let (min, max) = (self.get_min(), self.get_max());
+        if lo > hi || hi < min || lo > max { return Ok(false); }
+        if lo <= min && hi >= max { return Err(Failure); }
+        if lo <= min { return self.set_min(hi + 1); }
+        if hi >= max { return self.set_max(lo - 1); }
+        let mut changed = false;
+        let mut next = self.next_value(lo - 1);
+        loop {
+            match next {
+                Some(v) if v <= hi => {
+                    // strictly inside the bounds, this cannot fail
+                    let _ = self.remove(v);
+                    changed = true;
+                    next = self.next_value(v);
+                },
+                _ => return Ok(changed)
+            }
+        }
+    }
+
+    /// keeps only the values in intervals, which are sorted and disjoint
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<bool, Failure> {
+        let kept = intervals.iter().any(|&(lo, hi)| {
+            match self.next_value(lo - 1) {
+                Some(v) => v <= hi,
+                None => false
+            }
+        });
+        if !kept { return Err(Failure); }
+        // there are values left, so removing the others cannot fail
+        let mut changed = false;
+        let mut from = self.get_min();
+        for &(lo, hi) in intervals.iter() {
+            changed = try!(self.remove_range(from, lo - 1)) || changed;
+            from = hi + 1;
+        }
+        let max = self.get_max();
+        changed = try!(self.remove_range(from, max)) || changed;
+        Ok(changed)
+    }
+
+    /// keeps only the given values
+    fn restrict_to(&self, values: &[int]) -> Result<bool, Failure> {
+        self.intersect_with(runs(values).as_slice())
+    }
 }
 
 /// Representation of finite domains as a list of intervals, maintaining
@@ -66,12 +180,87 @@ struct BitDomain {
     dom: RefCell<BitDom>
 }
 
+/// Representation of finite domains as a bit vector of any length, made
+/// of 64 bit words, maintaining min and max for easy/quick access
+#[deriving(Clone)]
+struct BitSetDom {
+    min: int,
+    max: int,
+    offset: int,
+    words: Vec<u64>
+}
+
+#[deriving(Clone)]
+struct BitSetDomain {
+    dom: RefCell<BitSetDom>
+}
+
+/// Bit set owned by a propagator, saved on the trail like domains
+struct TrailedWords {
+    words: RefCell<Vec<u64>>,
+    stamp: Cell<uint>
+}
+
+/// Representation of finite domains switching from a list of intervals to
+/// a bit vector, of one or more words, as soon as the range of values is
+/// small enough, and back to intervals when a few wide runs are left in
+/// several words, backtracking restores the previous representation
+#[deriving(Clone)]
+struct AdaptiveDomain {
+    dom: RefCell<VarDomain>
+}
+
+/// Representation of finite domains as a sparse set: the values are the
+/// first size ones of dense, and sparse gives the position of each value in
+/// dense. Removing a value swaps it past the end of the set, so the values
+/// present at some point are still the first ones, in a different order,
+/// after further removals: clones share the arrays and restoring one only
+/// copies back the size and the bounds
+#[deriving(Clone)]
+struct SparseDomain {
+    arrays: Rc<RefCell<SparseArrays>>,
+    size: Cell<uint>,
+    min: Cell<int>,
+    max: Cell<int>
+}
+
+struct SparseArrays {
+    offset: int,
+    dense: Vec<int>,
+    sparse: Vec<uint>
+}
+
+/// largest range of an AdaptiveDomain represented with several words
+static ADAPTIVE_BITSET_RANGE: int = 1024;
+
+/// words an AdaptiveDomain needs per run of values to prefer several words
+/// to a list of intervals
+static ADAPTIVE_WORDS_PER_RUN: uint = 8;
+
+/// Domain of a variable, in one of the available representations
+#[deriving(Clone)]
+enum VarDomain {
+    Intervals(IntervalDomain),
+    Bits(BitDomain),
+    BitSet(BitSetDomain),
+    Sparse(SparseDomain),
+    /// never holds another Adaptive
+    Adaptive(Box<AdaptiveDomain>)
+}
+
 trait Propagator {
     fn id(&self) -> uint;
     fn model(&self) -> Weak<Mod>;
 
     fn events(&self) -> Vec<(uint, Event)>;
-    fn propagate(&self) -> Vec<uint>;
+    /// returns the ids of the propagators woken by the domain changes
+    fn propagate(&self) -> Result<Vec<uint>, Failure>;
+
+    /// propagators with a lower priority are run first, 0 is meant for
+    /// cheap bounds propagators and PRIORITIES - 1 for expensive globals
+    fn priority(&self) -> uint {
+        0
+    }
 
     fn register(&self) {
         for &(var, event) in self.events().iter() {
@@ -84,8 +273,17 @@ trait Propagator {
             self.model().upgrade().unwrap().del_waiting(var, event, self.id());
         }
     }
+
+    /// to be called when the constraint holds whatever the remaining values,
+    /// the propagator is not run again until backtracking
+    fn entail(&self) {
+        self.model().upgrade().unwrap().entail(self.id());
+    }
 }
 
+/// number of priorities of propagators
+static PRIORITIES: uint = 3;
+
 pub virtual struct Prop {
     id: uint,
     model: Weak<Mod>,
@@ -97,27 +295,60 @@ pub struct FDVar {
     model: Weak<Mod>,
     id: uint,
     name: String,
-    dom: IntervalDomain
+    /// unused by views, which read and modify the domain of their variable
+    dom: VarDomain,
+    stamp: Cell<uint>,
+    view: Option<View>,
+    /// ids of the views over this variable, woken along with it
+    views: RefCell<Vec<uint>>
+}
+
+/// a * var + b, with a != 0, var is never itself a view
+#[deriving(Clone)]
+struct View {
+    var: Rc<FDVar>,
+    a: int,
+    b: int
 }
 
 /// wrapping FDVar in an Rc
 pub struct Var;
 
+/// Iterator over the values of a variable
+pub struct Values<'a> {
+    var: &'a FDVar,
+    next: Option<int>
+}
+
 #[deriving(Show, Hash, Eq, PartialEq)]
 pub enum Event {
     Min,
     Max,
-    Ins
+    Ins,
+    /// any change, including removal of values strictly inside the bounds
+    Dom
 }
 
 #[allow(dead_code)]
 impl Model {
-    fn new() -> Rc<Mod> {
-        Rc::new(Mod {
+    pub fn new() -> Rc<Mod> {
+        let model = Rc::new(Mod {
             vars: RefCell::new(Vec::new()),
             propagators: RefCell::new(Vec::new()),
-            waiting: RefCell::new(HashMap::new())
-        })
+            failures: RefCell::new(Vec::new()),
+            waiting: RefCell::new(HashMap::new()),
+            queue: RefCell::new(Vec::from_fn(PRIORITIES, |_| RingBuf::new())),
+            queued: RefCell::new(Vec::new()),
+            entailed: RefCell::new(Vec::new()),
+            failed: Cell::new(false),
+            trail: RefCell::new(Vec::new()),
+            levels: RefCell::new(Vec::new()),
+            // stamp 0 is never used for a level, see FDVar::save
+            stamp: Cell::new(1),
+            me: RefCell::new(None)
+        });
+        *model.me.borrow_mut() = Some(model.downgrade());
+        model
     }
 }
 
@@ -128,9 +359,13 @@ impl Mod {
     }
 
     fn add_prop(&self, prop: Rc<Box<Propagator>>) {
+        self.trail_push(Added(prop.id()));
         self.propagators.borrow_mut().push(prop.clone());
+        self.failures.borrow_mut().push(0);
+        self.queued.borrow_mut().push(false);
+        self.entailed.borrow_mut().push(false);
         prop.register();
-        self.propagate(self.propagators.borrow().len() - 1);
+        let _ = self.propagate(self.propagators.borrow().len() - 1);
     }
 
     fn add_waiting(&self, var: uint, event: Event, propagator: uint) {
@@ -143,7 +378,45 @@ impl Mod {
     }
 
     fn del_waiting(&self, var: uint, event: Event, propagator: uint) {
-        self.waiting.borrow_mut().get_mut(&(var, event)).remove(propagator);
+        let removed = match self.waiting.borrow_mut().find_mut(&(var, event)) {
+            Some(props) => match props.iter().position(|&p| p == propagator) {
+                Some(i) => props.remove(i).is_some(),
+                None => false
+            },
+            None => false
+        };
+        if removed {
+            self.trail_push(Unregistered(var, event, propagator));
+        }
+    }
+
+    /// marks a propagator as entailed and removes it from the waiting lists,
+    /// both undone when popping the current level
+    fn entail(&self, propagator: uint) {
+        if self.is_entailed(propagator) { return; }
+        *self.entailed.borrow_mut().get_mut(propagator) = true;
+        self.trail_push(Entailed(propagator));
+        let prop = self.propagators.borrow().get(propagator).clone();
+        prop.unregister();
+    }
+
+    fn is_entailed(&self, propagator: uint) -> bool {
+        *self.entailed.borrow().get(propagator)
+    }
+
+    /// adds to woken the propagators waiting on the events that happened to
+    /// var
+    fn wake(&self, var: uint, min: bool, max: bool, fixed: bool, woken: &mut Vec<uint>) {
+        if min {
+            woken.push_all(self.get_waiting(var, Min).as_slice());
+        }
+        if max {
+            woken.push_all(self.get_waiting(var, Max).as_slice());
+        }
+        if fixed {
+            woken.push_all(self.get_waiting(var, Ins).as_slice());
+        }
+        woken.push_all(self.get_waiting(var, Dom).as_slice());
     }
 
     fn get_waiting(&self, var: uint, event: Event) -> Vec<uint> {
@@ -154,20 +427,211 @@ impl Mod {
         }
     }
 
-    fn propagate(&self, id: uint) {
-        println!("propagating for {}", id.to_str());
-        let woken = self.propagators.borrow().get(id).propagate();
-        println!("waking {}", woken.to_str());
-        for &propid in woken.iter() {
-            self.propagate(propid);
+    /// number of propagators waiting on var, whatever the events
+    pub fn degree(&self, var: uint) -> uint {
+        self.waiting_on(var).len()
+    }
+
+    /// sum over the propagators waiting on var of one plus their number of
+    /// failures, as used by dom/wdeg
+    pub fn weighted_degree(&self, var: uint) -> uint {
+        let failures = self.failures.borrow();
+        self.waiting_on(var).iter().fold(0, |n, &p| n + 1 + *failures.get(p))
+    }
+
+    /// distinct propagators waiting on var and not entailed
+    fn waiting_on(&self, var: uint) -> Vec<uint> {
+        let mut props = Vec::new();
+        for (&(v, _), ids) in self.waiting.borrow().iter() {
+            if v == var {
+                props.push_all(ids.as_slice());
+            }
+        }
+        props.sort();
+        props.dedup();
+        props.move_iter().filter(|&p| !self.is_entailed(p)).collect()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.failed.get()
+    }
+
+    fn fail(&self) {
+        if !self.is_failed() {
+            self.failed.set(true);
+            self.trail_push(Failed);
+        }
+    }
+
+    /// iterator over all the solutions labelling vars
+    pub fn solutions(&self, vars: Vec<Rc<FDVar>>) -> Search {
+        let me = self.me.borrow().as_ref().unwrap().upgrade().unwrap();
+        Search::new(me, vars)
+    }
+
+    /// number of solutions labelling vars
+    pub fn count_solutions(&self, vars: Vec<Rc<FDVar>>) -> uint {
+        self.solutions(vars).count_solutions()
+    }
+
+    /// number of levels pushed and not yet popped
+    pub fn level(&self) -> uint {
+        self.levels.borrow().len()
+    }
+
+    /// starts a new level, all changes made until the matching pop_level
+    /// will be undone by it
+    pub fn push_level(&self) {
+        let mark = self.trail.borrow().len();
+        self.levels.borrow_mut().push(mark);
+        self.stamp.set(self.stamp.get() + 1);
+    }
+
+    /// restores the model in the state it had at the matching push_level
+    pub fn pop_level(&self) {
+        let mark = self.levels.borrow_mut().pop().expect("no level to pop");
+        loop {
+            let entry = {
+                let mut trail = self.trail.borrow_mut();
+                if trail.len() <= mark { break; }
+                trail.pop().unwrap()
+            };
+            match entry {
+                SavedDomain(var, dom) => self.vars.borrow().get(var).dom.restore(&dom),
+                Unregistered(var, event, propagator) => self.add_waiting(var, event, propagator),
+                Entailed(propagator) => *self.entailed.borrow_mut().get_mut(propagator) = false,
+                Added(propagator) => self.remove_prop(propagator),
+                SavedWords(bits, words) => *bits.words.borrow_mut() = words,
+                Failed => self.failed.set(false)
+            }
+        }
+        self.stamp.set(self.stamp.get() + 1);
+    }
+
+    /// removes the last propagator posted, without recording anything on the
+    /// trail since this undoes its posting
+    fn remove_prop(&self, propagator: uint) {
+        let prop = self.propagators.borrow_mut().pop().unwrap();
+        assert!(prop.id() == propagator);
+        for &(var, event) in prop.events().iter() {
+            match self.waiting.borrow_mut().find_mut(&(var, event)) {
+                Some(props) => props.retain(|&p| p != propagator),
+                None => ()
+            }
+        }
+        self.failures.borrow_mut().pop();
+        self.queued.borrow_mut().pop();
+        self.entailed.borrow_mut().pop();
+    }
+
+    /// records the words on the trail before their first change in a level
+    fn save_words(&self, bits: &Rc<TrailedWords>) {
+        if bits.stamp.get() != self.stamp.get() {
+            bits.stamp.set(self.stamp.get());
+            self.trail_push(SavedWords(bits.clone(), bits.words.borrow().clone()));
+        }
+    }
+
+    /// changes done at the root level are never undone, hence not recorded
+    fn trail_push(&self, entry: Trailed) {
+        if self.level() > 0 {
+            self.trail.borrow_mut().push(entry);
+        }
+    }
+
+    fn propagate(&self, id: uint) -> Result<(), Failure> {
+        self.propagate_vec(vec![id])
+    }
+
+    fn propagate_vec(&self, ids: Vec<uint>) -> Result<(), Failure> {
+        self.schedule(ids.as_slice());
+        self.propagate_to_fixpoint()
+    }
+
+    /// adds propagators to the queue, unless they already are in it or are
+    /// entailed
+    fn schedule(&self, ids: &[uint]) {
+        let propagators = self.propagators.borrow();
+        let entailed = self.entailed.borrow();
+        let mut queue = self.queue.borrow_mut();
+        let mut queued = self.queued.borrow_mut();
+        for &id in ids.iter() {
+            if !*queued.get(id) && !*entailed.get(id) {
+                *queued.get_mut(id) = true;
+                queue.get_mut(propagators.get(id).priority()).push_back(id);
+            }
+        }
+    }
+
+    /// next propagator of the lowest priority scheduled
+    fn unschedule(&self) -> Option<uint> {
+        let mut queue = self.queue.borrow_mut();
+        for fifo in queue.mut_iter() {
+            match fifo.pop_front() {
+                Some(id) => {
+                    *self.queued.borrow_mut().get_mut(id) = false;
+                    return Some(id);
+                },
+                None => ()
+            }
+        }
+        None
+    }
+
+    /// runs the scheduled propagators, and those they wake, until there is
+    /// none left, stops at the first failure which is then recorded in the
+    /// model
+    pub fn propagate_to_fixpoint(&self) -> Result<(), Failure> {
+        loop {
+            if self.is_failed() {
+                while self.unschedule().is_some() {}
+                return Err(Failure);
+            }
+            let id = match self.unschedule() {
+                Some(id) => id,
+                None => return Ok(())
+            };
+            let result = self.propagators.borrow().get(id).propagate();
+            match result {
+                Ok(woken) => self.schedule(woken.as_slice()),
+                Err(_) => {
+                    *self.failures.borrow_mut().get_mut(id) += 1;
+                    self.fail();
+                }
+            }
+        }
+    }
+
+    /// propagates the outcome of a direct domain modification, as done when
+    /// posting unary constraints
+    fn post(&self, woken: Result<Vec<uint>, Failure>) {
+        match woken {
+            Ok(ids) => { let _ = self.propagate_vec(ids); },
+            Err(_) => self.fail()
         }
     }
+}
+
+impl TrailedWords {
+    fn new(words: Vec<u64>) -> Rc<TrailedWords> {
+        Rc::new(TrailedWords { words: RefCell::new(words), stamp: Cell::new(0) })
+    }
+}
 
-    fn propagate_vec(&self, ids: Vec<uint>) {
-        println!("propagating for {}", ids.to_str());
-        for &propid in ids.iter() {
-            self.propagate(propid);
+impl IntervalDom {
+    /// index of the first interval ending at or after val, the number of
+    /// intervals if there is none
+    fn first_ending_from(&self, val: int) -> uint {
+        let mut down = 0;
+        let mut up = self.intervals.len();
+        while down < up {
+            let test = down + (up - down) / 2;
+            match self.intervals.get(test) {
+                &(_, y) if y < val => down = test + 1,
+                _ => up = test
+            }
         }
+        down
     }
 }
 
@@ -184,10 +648,14 @@ impl Domain for IntervalDomain {
         }
     }
 
-    fn set_min(&self, min: int) {
+    fn restore(&self, saved: &IntervalDomain) {
+        *self.dom.borrow_mut() = saved.dom.borrow().clone();
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
         let mut dom = self.dom.borrow_mut();
-        if min < dom.min { return; }
-        if min > dom.max { return; } // TODO failure via conditions
+        if min <= dom.min { return Ok(false); }
+        if min > dom.max { return Err(Failure); }
         loop {
             match dom.intervals.get(0) {
                 // note that the breaks are for the loop, not the matching
@@ -200,16 +668,17 @@ impl Domain for IntervalDomain {
                 }
             }
         }
+        Ok(true)
     }
 
     fn get_min(&self) -> int {
         self.dom.borrow().min
     }
 
-    fn set_max(&self, max: int) {
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
         let mut dom = self.dom.borrow_mut();
-        if max > dom.max { return; }
-        if max < dom.min { return; } // TODO failure via conditions
+        if max >= dom.max { return Ok(false); }
+        if max < dom.min { return Err(Failure); }
         loop {
             match dom.intervals.last().unwrap() {
                 &(_, y) if max > y => { dom.max = y; break; },
@@ -221,16 +690,17 @@ impl Domain for IntervalDomain {
                 }
             }
         }
+        Ok(true)
     }
 
     fn get_max(&self) -> int {
         self.dom.borrow().max
     }
 
-    // TODO test for emptyness
-    fn remove(&self, val: int) {
+    fn remove(&self, val: int) -> Result<bool, Failure> {
         let mut dom = self.dom.borrow_mut();
-        if val > dom.max || val < dom.min { return; }
+        if val > dom.max || val < dom.min { return Ok(false); }
+        if dom.min == dom.max { return Err(Failure); }
         let mut down = 0;
         let mut up = dom.intervals.len();
         let mut test;
@@ -241,14 +711,14 @@ impl Domain for IntervalDomain {
                     if test > down {
                         up = test;
                     } else {
-                        break;
+                        return Ok(false);
                     }
                 },
                 &(_, y) if val > y => {
                     if test < up - 1 {
                         down = test + 1;
                     } else {
-                        break;
+                        return Ok(false);
                     }
                 },
                 &(x, y) if val == x && val == y => {
@@ -270,14 +740,84 @@ impl Domain for IntervalDomain {
                 }
             }
         }
-        if test == 0 {
-            match dom.intervals.get(test) {
-                &(x, _) => dom.min = x
-            }
-        } else if test == dom.intervals.len() - 1 {
-            match dom.intervals.get(test) {
-                &(_, y) => dom.max = y
+        // only the first and last intervals can have moved the bounds
+        let (min, _) = *dom.intervals.get(0);
+        let (_, max) = *dom.intervals.last().unwrap();
+        dom.min = min;
+        dom.max = max;
+        Ok(true)
+    }
+
+    fn size(&self) -> uint {
+        self.dom.borrow().intervals.iter().fold(0, |n, &(x, y)| n + (y - x + 1) as uint)
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        self.dom.borrow().intervals.clone()
+    }
+
+    /// merges both lists of intervals
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<bool, Failure> {
+        let mut dom = self.dom.borrow_mut();
+        let mut merged = Vec::new();
+        let (mut i, mut j) = (0, 0);
+        while i < dom.intervals.len() && j < intervals.len() {
+            let &(x, y) = dom.intervals.get(i);
+            let (lo, hi) = intervals[j];
+            if max(x, lo) <= min(y, hi) {
+                let (from, to) = (max(x, lo), min(y, hi));
+                let adjacent = match merged.last() {
+                    Some(&(_, end)) => end + 1 == from,
+                    None => false
+                };
+                if adjacent {
+                    // adjacent intervals given are merged
+                    let (start, _) = merged.pop().unwrap();
+                    merged.push((start, to));
+                } else {
+                    merged.push((from, to));
+                }
             }
+            if y < hi { i += 1; } else { j += 1; }
+        }
+        if merged.is_empty() { return Err(Failure); }
+        if merged == dom.intervals { return Ok(false); }
+        let (min, _) = *merged.get(0);
+        let (_, max) = *merged.last().unwrap();
+        dom.min = min;
+        dom.max = max;
+        dom.intervals = merged;
+        Ok(true)
+    }
+
+    fn remove_range(&self, lo: int, hi: int) -> Result<bool, Failure> {
+        let (min, max) = (self.get_min(), self.get_max());
+        if lo > hi || hi < min || lo > max { return Ok(false); }
+        self.intersect_with(&[(min, lo - 1), (hi + 1, max)])
+    }
+
+    fn contains(&self, val: int) -> bool {
+        let dom = self.dom.borrow();
+        let i = dom.first_ending_from(val);
+        i < dom.intervals.len() && match dom.intervals.get(i) { &(x, _) => x <= val }
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val >= dom.max { return None; }
+        let &(x, _) = dom.intervals.get(dom.first_ending_from(val + 1));
+        Some(max(x, val + 1))
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val <= dom.min { return None; }
+        let i = dom.first_ending_from(val - 1);
+        if i < dom.intervals.len() && match dom.intervals.get(i) { &(x, _) => x < val } {
+            Some(val - 1)
+        } else {
+            let &(_, y) = dom.intervals.get(i - 1);
+            Some(y)
         }
     }
 }
@@ -294,14 +834,59 @@ impl fmt::Show for IntervalDomain {
 }
 
 impl Var {
+    /// variable with a bit vector domain if its range is small enough, a
+    /// list of intervals otherwise
     pub fn new(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
         assert!(min <= max);
+        Var::with_domain(model, Domain::new(min, max), name)
+    }
+
+    /// 0/1 variable, for false and true, to reify constraints
+    pub fn new_bool(model: Rc<Mod>, name: &str) -> Rc<FDVar> {
+        Var::new_bits(model, 0, 1, name)
+    }
+
+    /// variable with a bit vector domain, max - min must be less than 64
+    pub fn new_bits(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
+        assert!(min <= max);
+        Var::with_domain(model, Bits(Domain::new(min, max)), name)
+    }
+
+    /// variable with a domain represented as a bit vector of any length
+    pub fn new_bitset(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
+        assert!(min <= max);
+        Var::with_domain(model, BitSet(Domain::new(min, max)), name)
+    }
+
+    /// variable with a sparse set domain, cheap to restore when backtracking
+    pub fn new_sparse(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
+        assert!(min <= max);
+        Var::with_domain(model, Sparse(Domain::new(min, max)), name)
+    }
+
+    /// variable with a domain represented as a list of intervals until its
+    /// range is small enough for a bit vector
+    pub fn new_adaptive(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
+        assert!(min <= max);
+        Var::with_domain(model, Adaptive(box Domain::new(min, max)), name)
+    }
+
+    /// variable with a domain represented as a list of intervals
+    pub fn new_intervals(model: Rc<Mod>, min: int, max: int, name: &str) -> Rc<FDVar> {
+        assert!(min <= max);
+        Var::with_domain(model, Intervals(Domain::new(min, max)), name)
+    }
+
+    fn with_domain(model: Rc<Mod>, dom: VarDomain, name: &str) -> Rc<FDVar> {
         let id = model.vars.borrow().len();
         let v = Rc::new(FDVar {
             model: model.downgrade(),
             id: id,
             name: name.to_string(),
-            dom: Domain::new(min, max)
+            dom: dom,
+            stamp: Cell::new(0),
+            view: None,
+            views: RefCell::new(Vec::new())
         });
         model.add_var(v.clone());
         v
@@ -310,127 +895,1166 @@ impl Var {
 
 impl FDVar {
     pub fn min(&self) -> int {
-        self.dom.get_min()
+        match self.view {
+            Some(ref view) => view.min(),
+            None => self.dom.get_min()
+        }
     }
 
     pub fn max(&self) -> int {
-        self.dom.get_max()
+        match self.view {
+            Some(ref view) => view.max(),
+            None => self.dom.get_max()
+        }
     }
 
-    fn set_min(&self, v: int) -> Vec<uint> {
-        if v > self.min() {
-            self.dom.set_min(v);
-            let model = self.model.upgrade().unwrap();
-            if self.is_instanciated() {
-                model.get_waiting(self.id, Min).append(model.get_waiting(self.id, Ins).as_slice())
-            } else {
-                model.get_waiting(self.id, Min)
-            }
+    /// view x + c
+    pub fn offset(&self, c: int) -> Rc<FDVar> {
+        self.affine(1, c, format!("{} + {}", self.name, c))
+    }
+
+    /// view a * x, a must not be 0
+    pub fn scale(&self, a: int) -> Rc<FDVar> {
+        assert!(a != 0, "cannot scale a variable by 0");
+        self.affine(a, 0, format!("{} * {}", a, self.name))
+    }
+
+    /// view -x
+    pub fn neg(&self) -> Rc<FDVar> {
+        self.affine(-1, 0, format!("-{}", self.name))
+    }
+
+    /// view a * x + b, registered as a new variable of the model, views of
+    /// views are views of the underlying variable
+    fn affine(&self, a: int, b: int, name: String) -> Rc<FDVar> {
+        let model = self.model.upgrade().unwrap();
+        let view = match self.view {
+            Some(ref v) => View { var: v.var.clone(), a: a * v.a, b: a * v.b + b },
+            None => View { var: model.vars.borrow().get(self.id).clone(), a: a, b: b }
+        };
+        let id = model.vars.borrow().len();
+        view.var.views.borrow_mut().push(id);
+        let v = Rc::new(FDVar {
+            model: self.model.clone(),
+            id: id,
+            name: name,
+            dom: Bits(Domain::new(0, 0)),
+            stamp: Cell::new(0),
+            view: Some(view),
+            views: RefCell::new(Vec::new())
+        });
+        model.add_var(v.clone());
+        v
+    }
+
+    fn set_min(&self, v: int) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => return view.set_min(v),
+            None => ()
+        }
+        if v <= self.min() { return Ok(vec![]); }
+        self.update(|dom| dom.set_min(v))
+    }
+
+    fn set_max(&self, v: int) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => return view.set_max(v),
+            None => ()
+        }
+        if v >= self.max() { return Ok(vec![]); }
+        self.update(|dom| dom.set_max(v))
+    }
+
+    fn remove(&self, v: int) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => return view.remove(v),
+            None => ()
+        }
+        if !self.contains(v) { return Ok(vec![]); }
+        self.update(|dom| dom.remove(v))
+    }
+
+    fn remove_range(&self, lo: int, hi: int) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => return view.remove_range(lo, hi),
+            None => ()
+        }
+        if hi < self.min() || lo > self.max() { return Ok(vec![]); }
+        self.update(|dom| dom.remove_range(lo, hi))
+    }
+
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => view.intersect_with(intervals),
+            None => self.update(|dom| dom.intersect_with(intervals))
+        }
+    }
+
+    fn restrict_to(&self, values: &[int]) -> Result<Vec<uint>, Failure> {
+        match self.view {
+            Some(ref view) => view.restrict_to(values),
+            None => self.update(|dom| dom.restrict_to(values))
+        }
+    }
+
+    /// applies a change to the domain, which is saved first, and returns the
+    /// propagators woken by all the events it caused
+    fn update(&self, change: |&VarDomain| -> Result<bool, Failure>) -> Result<Vec<uint>, Failure> {
+        self.save();
+        let (min, max) = (self.min(), self.max());
+        if try!(change(&self.dom)) {
+            Ok(self.woken(min, max))
         } else {
-            vec![]
+            Ok(vec![])
         }
     }
 
-    fn set_max(&self, v: int) -> Vec<uint> {
-        if v < self.max() {
-            self.dom.set_max(v);
-            let model = self.model.upgrade().unwrap();
-            if self.is_instanciated() {
-                model.get_waiting(self.id, Max).append(model.get_waiting(self.id, Ins).as_slice())
+    /// propagators waiting on the bounds that moved from min and max, on
+    /// instanciation if it happened, and on any change, of this variable and
+    /// of its views
+    fn woken(&self, min: int, max: int) -> Vec<uint> {
+        let model = self.model.upgrade().unwrap();
+        let (new_min, new_max) = (self.min() != min, self.max() != max);
+        let fixed = self.is_instanciated();
+        let mut woken = Vec::new();
+        model.wake(self.id, new_min, new_max, fixed, &mut woken);
+        for &id in self.views.borrow().iter() {
+            let reversed = match model.vars.borrow().get(id).view {
+                Some(ref view) => view.a < 0,
+                None => false
+            };
+            if reversed {
+                model.wake(id, new_max, new_min, fixed, &mut woken);
             } else {
-                model.get_waiting(self.id, Max)
+                model.wake(id, new_min, new_max, fixed, &mut woken);
             }
-        } else {
-            vec![]
         }
+        woken
     }
 
-    fn remove(&self, v: int) -> Vec<uint> {
-        let min = self.min();
-        let max = self.max();
-        match v {
-            vv if vv < min || vv > max => vec![],
-            vv if vv == min => self.set_min(vv + 1),
-            vv if vv == max => self.set_max(vv - 1),
-            _ => {
-                self.dom.remove(v);
-                vec![]
-            }
+    /// records the domain on the trail before its first change in a level
+    fn save(&self) {
+        let model = self.model.upgrade().unwrap();
+        if self.stamp.get() != model.stamp.get() {
+            self.stamp.set(model.stamp.get());
+            model.trail_push(SavedDomain(self.id, self.dom.clone()));
         }
     }
 
-    fn is_instanciated(&self) -> bool {
-        self.min() == self.max()
+    pub fn name(&self) -> &str {
+        self.name.as_slice()
     }
-}
 
-impl fmt::Show for FDVar {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{} ({})", self.name, self.dom)
+    /// number of values in the domain
+    pub fn size(&self) -> uint {
+        match self.view {
+            Some(ref view) => view.var.size(),
+            None => self.dom.size()
+        }
     }
-}
 
-impl Domain for BitDomain {
-    fn new(min: int, max: int) -> BitDomain {
-        assert!(max - min < 64, "cannot be represented as a 64 bit vector");
-        BitDomain {
-            dom: RefCell::new(BitDom {
-                     min: min,
-                     max: max,
-                     offset: min,
-                     bitvector: ! 0_u64 >> ((63 - max + min) as uint)
-                 })
+    pub fn contains(&self, v: int) -> bool {
+        match self.view {
+            Some(ref view) => view.contains(v),
+            None => self.dom.contains(v)
         }
     }
 
-    fn set_min(&self, min: int) {
-        let mut dom = self.dom.borrow_mut();
-        if min < dom.min { return; }
-        if min > dom.max { return; } // TODO failure via conditions
-        // FIXME improve
-        dom.min = min;
-        dom.bitvector &= (! 0_u64 >> (dom.min - dom.offset) as uint) << (dom.min - dom.offset) as uint
+    pub fn is_fixed(&self) -> bool {
+        self.is_instanciated()
     }
 
-    fn get_min(&self) -> int {
-        self.dom.borrow().min
+    /// the value of a fixed variable
+    pub fn value(&self) -> Option<int> {
+        if self.is_fixed() { Some(self.min()) } else { None }
     }
 
-    fn set_max(&self, max: int) {
-        let mut dom = self.dom.borrow_mut();
-        if max > dom.max { return; }
-        if max < dom.min { return; } // TODO failure via conditions
-        // FIXME improve
-        dom.max = max;
-        dom.bitvector &= ! 0_u64 >> ((63 - max + dom.min) as uint);
-        while ((1 << (dom.max - dom.min) as uint) & dom.bitvector) == 0 {
-            dom.max -= 1;
+    /// smallest value of the domain greater than v
+    pub fn next_value_after(&self, v: int) -> Option<int> {
+        match self.view {
+            Some(ref view) => view.next_value(v),
+            None => self.dom.next_value(v)
         }
     }
 
-    fn get_max(&self) -> int {
-        self.dom.borrow().max
+    /// largest value of the domain less than v
+    pub fn prev_value_before(&self, v: int) -> Option<int> {
+        match self.view {
+            Some(ref view) => view.prev_value(v),
+            None => self.dom.prev_value(v)
+        }
     }
 
-    // TODO test for emptyness
-    fn remove(&self, val: int) {
-        let mut dom = self.dom.borrow_mut();
-        if val > dom.max || val < dom.min { return; }
-        if val == dom.max { self.set_max(val - 1); return; }
-        if val == dom.min { self.set_min(val + 1); return; }
-        dom.bitvector ^= 1 << ((val - dom.offset) as uint)
+    /// values of the domain in increasing order, the iterator follows
+    /// changes made to the domain while iterating
+    pub fn values<'a>(&'a self) -> Values<'a> {
+        Values { var: self, next: Some(self.min()) }
+    }
+
+    /// maximal intervals of consecutive values, in increasing order, as
+    /// they are when calling this
+    pub fn intervals(&self) -> MoveItems<(int, int)> {
+        match self.view {
+            Some(ref view) => view.intervals().move_iter(),
+            None => self.dom.intervals().move_iter()
+        }
+    }
+
+    fn is_instanciated(&self) -> bool {
+        self.min() == self.max()
     }
 }
 
-impl fmt::Show for BitDomain {
+impl<'a> Iterator<int> for Values<'a> {
+    fn next(&mut self) -> Option<int> {
+        let current = self.next;
+        match current {
+            Some(v) => self.next = self.var.next_value_after(v),
+            None => ()
+        }
+        current
+    }
+}
+
+impl fmt::Show for FDVar {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let dom = self.dom.borrow();
-        return write!(f, "({}, {}) [{:t}]", dom.min, dom.max, dom.bitvector);
+        match self.view {
+            Some(_) => write!(f, "{} ({})", self.name, self.intervals().collect::<Vec<(int, int)>>()),
+            None => write!(f, "{} ({})", self.name, self.dom)
+        }
+    }
+}
+
+impl View {
+    fn image(&self, v: int) -> int {
+        self.a * v + self.b
+    }
+
+    /// values of var whose images are between lo and hi, possibly empty
+    fn preimage(&self, lo: int, hi: int) -> (int, int) {
+        if self.a > 0 {
+            (div_ceil(lo - self.b, self.a), div_floor(hi - self.b, self.a))
+        } else {
+            (div_ceil(hi - self.b, self.a), div_floor(lo - self.b, self.a))
+        }
+    }
+
+    /// value of var with v as image, if any
+    fn antecedent(&self, v: int) -> Option<int> {
+        if (v - self.b) % self.a == 0 { Some((v - self.b) / self.a) } else { None }
+    }
+
+    fn min(&self) -> int {
+        if self.a > 0 { self.image(self.var.min()) } else { self.image(self.var.max()) }
+    }
+
+    fn max(&self) -> int {
+        if self.a > 0 { self.image(self.var.max()) } else { self.image(self.var.min()) }
+    }
+
+    fn set_min(&self, v: int) -> Result<Vec<uint>, Failure> {
+        if self.a > 0 {
+            self.var.set_min(div_ceil(v - self.b, self.a))
+        } else {
+            self.var.set_max(div_floor(v - self.b, self.a))
+        }
+    }
+
+    fn set_max(&self, v: int) -> Result<Vec<uint>, Failure> {
+        if self.a > 0 {
+            self.var.set_max(div_floor(v - self.b, self.a))
+        } else {
+            self.var.set_min(div_ceil(v - self.b, self.a))
+        }
+    }
+
+    fn remove(&self, v: int) -> Result<Vec<uint>, Failure> {
+        match self.antecedent(v) {
+            Some(w) => self.var.remove(w),
+            None => Ok(vec![])
+        }
+    }
+
+    fn remove_range(&self, lo: int, hi: int) -> Result<Vec<uint>, Failure> {
+        let (lo, hi) = self.preimage(lo, hi);
+        if lo > hi { return Ok(vec![]); }
+        self.var.remove_range(lo, hi)
+    }
+
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<Vec<uint>, Failure> {
+        let mut mapped: Vec<(int, int)> = Vec::new();
+        for &(lo, hi) in intervals.iter() {
+            let (lo, hi) = self.preimage(lo, hi);
+            if lo <= hi { mapped.push((lo, hi)); }
+        }
+        if self.a < 0 { mapped.reverse(); }
+        self.var.intersect_with(mapped.as_slice())
+    }
+
+    fn restrict_to(&self, values: &[int]) -> Result<Vec<uint>, Failure> {
+        let mapped: Vec<int> = values.iter().filter_map(|&v| self.antecedent(v)).collect();
+        self.var.restrict_to(mapped.as_slice())
+    }
+
+    fn contains(&self, v: int) -> bool {
+        match self.antecedent(v) {
+            Some(w) => self.var.contains(w),
+            None => false
+        }
+    }
+
+    fn next_value(&self, v: int) -> Option<int> {
+        if self.a > 0 {
+            self.var.next_value_after(div_floor(v - self.b, self.a)).map(|w| self.image(w))
+        } else {
+            self.var.prev_value_before(div_ceil(v - self.b, self.a)).map(|w| self.image(w))
+        }
+    }
+
+    fn prev_value(&self, v: int) -> Option<int> {
+        if self.a > 0 {
+            self.var.prev_value_before(div_ceil(v - self.b, self.a)).map(|w| self.image(w))
+        } else {
+            self.var.next_value_after(div_floor(v - self.b, self.a)).map(|w| self.image(w))
+        }
+    }
+
+    /// images of the intervals of var, which are single values unless a is
+    /// 1 or -1
+    fn intervals(&self) -> Vec<(int, int)> {
+        let mut intervals: Vec<(int, int)> = if self.a == 1 || self.a == -1 {
+            self.var.intervals().map(|(lo, hi)| {
+                let (x, y) = (self.image(lo), self.image(hi));
+                (min(x, y), max(x, y))
+            }).collect()
+        } else {
+            self.var.values().map(|v| (self.image(v), self.image(v))).collect()
+        };
+        if self.a < 0 { intervals.reverse(); }
+        intervals
+    }
+}
+
+/// floor of n / d
+fn div_floor(n: int, d: int) -> int {
+    let q = n / d;
+    if n % d != 0 && (n < 0) != (d < 0) { q - 1 } else { q }
+}
+
+/// ceiling of n / d
+fn div_ceil(n: int, d: int) -> int {
+    -div_floor(-n, d)
+}
+
+impl Domain for BitDomain {
+    fn new(min: int, max: int) -> BitDomain {
+        assert!(max - min < 64, "cannot be represented as a 64 bit vector");
+        BitDomain {
+            dom: RefCell::new(BitDom {
+                     min: min,
+                     max: max,
+                     offset: min,
+                     bitvector: ! 0_u64 >> ((63 - max + min) as uint)
+                 })
+        }
+    }
+
+    fn restore(&self, saved: &BitDomain) {
+        *self.dom.borrow_mut() = saved.dom.borrow().clone();
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
+        let mut dom = self.dom.borrow_mut();
+        if min <= dom.min { return Ok(false); }
+        if min > dom.max { return Err(Failure); }
+        // FIXME improve
+        dom.min = min;
+        dom.bitvector &= (! 0_u64 >> (dom.min - dom.offset) as uint) << (dom.min - dom.offset) as uint;
+        while ((1 << (dom.min - dom.offset) as uint) & dom.bitvector) == 0 {
+            dom.min += 1;
+        }
+        Ok(true)
+    }
+
+    fn get_min(&self) -> int {
+        self.dom.borrow().min
+    }
+
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
+        let mut dom = self.dom.borrow_mut();
+        if max >= dom.max { return Ok(false); }
+        if max < dom.min { return Err(Failure); }
+        // FIXME improve
+        dom.max = max;
+        dom.bitvector &= ! 0_u64 >> ((63 - max + dom.offset) as uint);
+        while ((1 << (dom.max - dom.offset) as uint) & dom.bitvector) == 0 {
+            dom.max -= 1;
+        }
+        Ok(true)
+    }
+
+    fn get_max(&self) -> int {
+        self.dom.borrow().max
+    }
+
+    fn remove(&self, val: int) -> Result<bool, Failure> {
+        let (min, max) = {
+            let dom = self.dom.borrow();
+            (dom.min, dom.max)
+        };
+        if val > max || val < min { return Ok(false); }
+        if val == max { return self.set_max(val - 1); }
+        if val == min { return self.set_min(val + 1); }
+        let mut dom = self.dom.borrow_mut();
+        let bit = 1 << ((val - dom.offset) as uint);
+        if dom.bitvector & bit == 0 { return Ok(false); }
+        dom.bitvector ^= bit;
+        Ok(true)
+    }
+
+    fn size(&self) -> uint {
+        count_bits(self.dom.borrow().bitvector)
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        let dom = self.dom.borrow();
+        let mut intervals = Vec::new();
+        let mut start = None;
+        for v in range(dom.min, dom.max + 2) {
+            let present = v <= dom.max && (dom.bitvector & (1 << ((v - dom.offset) as uint))) != 0;
+            match (start, present) {
+                (None, true) => start = Some(v),
+                (Some(s), false) => {
+                    intervals.push((s, v - 1));
+                    start = None;
+                },
+                _ => ()
+            }
+        }
+        intervals
+    }
+
+    fn contains(&self, val: int) -> bool {
+        let dom = self.dom.borrow();
+        val >= dom.min && val <= dom.max
+            && (dom.bitvector >> ((val - dom.offset) as uint)) & 1 == 1
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val >= dom.max { return None; }
+        if val < dom.min { return Some(dom.min); }
+        let i = (val + 1 - dom.offset) as uint;
+        Some(dom.offset + lowest_bit(dom.bitvector & (! 0_u64 << i)) as int)
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val <= dom.min { return None; }
+        if val > dom.max { return Some(dom.max); }
+        let i = (val - 1 - dom.offset) as uint;
+        Some(dom.offset + highest_bit(dom.bitvector & (! 0_u64 >> (63 - i))) as int)
+    }
+}
+
+impl fmt::Show for BitDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dom = self.dom.borrow();
+        return write!(f, "({}, {}) [{:t}]", dom.min, dom.max, dom.bitvector);
+    }
+}
+
+/// maximal intervals of consecutive values among values, in increasing
+/// order
+fn runs(values: &[int]) -> Vec<(int, int)> {
+    let mut sorted = values.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    let mut intervals: Vec<(int, int)> = Vec::new();
+    for &v in sorted.iter() {
+        match intervals.mut_last() {
+            Some(&(_, ref mut hi)) if *hi == v - 1 => { *hi = v; continue; },
+            _ => ()
+        }
+        intervals.push((v, v));
+    }
+    intervals
+}
+
+/// index of the lowest bit set in a non null word
+fn lowest_bit(word: u64) -> uint {
+    let mut i = 0;
+    while (word >> i) & 1 == 0 {
+        i += 1;
+    }
+    i
+}
+
+/// index of the highest bit set in a non null word
+fn highest_bit(word: u64) -> uint {
+    let mut i = 63;
+    while (word >> i) & 1 == 0 {
+        i -= 1;
+    }
+    i
+}
+
+fn count_bits(word: u64) -> uint {
+    let mut bits = word;
+    let mut n = 0;
+    while bits != 0 {
+        bits &= bits - 1;
+        n += 1;
+    }
+    n
+}
+
+impl BitSetDom {
+    fn has(&self, val: int) -> bool {
+        let i = (val - self.offset) as uint;
+        (*self.words.get(i / 64) >> (i % 64)) & 1 == 1
+    }
+
+    /// first value present from val on, there must be one
+    fn next_from(&self, val: int) -> int {
+        let i = (val - self.offset) as uint;
+        let mut w = i / 64;
+        let mut word = *self.words.get(w) & (! 0_u64 << (i % 64));
+        while word == 0 {
+            w += 1;
+            word = *self.words.get(w);
+        }
+        self.offset + (w * 64 + lowest_bit(word)) as int
+    }
+
+    /// first value absent from val on, possibly past the last word
+    fn next_absent_from(&self, val: int) -> int {
+        let i = (val - self.offset) as uint;
+        let mut w = i / 64;
+        let mut word = ! *self.words.get(w) & (! 0_u64 << (i % 64));
+        while word == 0 {
+            w += 1;
+            if w == self.words.len() { break; }
+            word = ! *self.words.get(w);
+        }
+        if word == 0 { return self.offset + (w * 64) as int; }
+        self.offset + (w * 64 + lowest_bit(word)) as int
+    }
+
+    /// last value present up to val, there must be one
+    fn prev_from(&self, val: int) -> int {
+        let i = (val - self.offset) as uint;
+        let mut w = i / 64;
+        let mut word = *self.words.get(w) & (! 0_u64 >> (63 - i % 64));
+        while word == 0 {
+            w -= 1;
+            word = *self.words.get(w);
+        }
+        self.offset + (w * 64 + highest_bit(word)) as int
+    }
+}
+
+impl BitSetDomain {
+    /// number of runs of consecutive values, the intervals of the domain
+    fn count_runs(&self) -> uint {
+        let dom = self.dom.borrow();
+        let mut carry = 0;
+        let mut runs = 0;
+        for &word in dom.words.iter() {
+            // bits set whose lower neighbour is not
+            runs += count_bits(word & !(word << 1 | carry));
+            carry = word >> 63;
+        }
+        runs
+    }
+}
+
+impl Domain for BitSetDomain {
+    fn new(min: int, max: int) -> BitSetDomain {
+        let size = (max - min + 1) as uint;
+        let mut words = Vec::from_elem((size + 63) / 64, ! 0_u64);
+        if size % 64 != 0 {
+            *words.mut_last().unwrap() = ! 0_u64 >> (64 - size % 64);
+        }
+        BitSetDomain {
+            dom: RefCell::new(BitSetDom {
+                     min: min,
+                     max: max,
+                     offset: min,
+                     words: words
+                 })
+        }
+    }
+
+    fn restore(&self, saved: &BitSetDomain) {
+        *self.dom.borrow_mut() = saved.dom.borrow().clone();
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
+        let mut dom = self.dom.borrow_mut();
+        if min <= dom.min { return Ok(false); }
+        if min > dom.max { return Err(Failure); }
+        let first = ((dom.min - dom.offset) as uint) / 64;
+        let i = (min - dom.offset) as uint;
+        for w in range(first, i / 64) {
+            *dom.words.get_mut(w) = 0;
+        }
+        *dom.words.get_mut(i / 64) &= ! 0_u64 << (i % 64);
+        let next = dom.next_from(min);
+        dom.min = next;
+        Ok(true)
+    }
+
+    fn get_min(&self) -> int {
+        self.dom.borrow().min
+    }
+
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
+        let mut dom = self.dom.borrow_mut();
+        if max >= dom.max { return Ok(false); }
+        if max < dom.min { return Err(Failure); }
+        let last = ((dom.max - dom.offset) as uint) / 64;
+        let i = (max - dom.offset) as uint;
+        for w in range(i / 64 + 1, last + 1) {
+            *dom.words.get_mut(w) = 0;
+        }
+        *dom.words.get_mut(i / 64) &= ! 0_u64 >> (63 - i % 64);
+        let prev = dom.prev_from(max);
+        dom.max = prev;
+        Ok(true)
+    }
+
+    fn get_max(&self) -> int {
+        self.dom.borrow().max
+    }
+
+    fn remove(&self, val: int) -> Result<bool, Failure> {
+        let (min, max) = {
+            let dom = self.dom.borrow();
+            (dom.min, dom.max)
+        };
+        if val > max || val < min { return Ok(false); }
+        if val == max { return self.set_max(val - 1); }
+        if val == min { return self.set_min(val + 1); }
+        let mut dom = self.dom.borrow_mut();
+        if !dom.has(val) { return Ok(false); }
+        let i = (val - dom.offset) as uint;
+        *dom.words.get_mut(i / 64) ^= 1 << (i % 64);
+        Ok(true)
+    }
+
+    fn size(&self) -> uint {
+        self.dom.borrow().words.iter().fold(0, |n, &word| n + count_bits(word))
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        let dom = self.dom.borrow();
+        let mut intervals = Vec::new();
+        let mut start = dom.min;
+        loop {
+            // the values past the max are absent
+            let end = dom.next_absent_from(start) - 1;
+            intervals.push((start, end));
+            if end == dom.max { break; }
+            start = dom.next_from(end + 1);
+        }
+        intervals
+    }
+
+    fn contains(&self, val: int) -> bool {
+        let dom = self.dom.borrow();
+        val >= dom.min && val <= dom.max && dom.has(val)
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val >= dom.max { return None; }
+        if val < dom.min { return Some(dom.min); }
+        Some(dom.next_from(val + 1))
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        let dom = self.dom.borrow();
+        if val <= dom.min { return None; }
+        if val > dom.max { return Some(dom.max); }
+        Some(dom.prev_from(val - 1))
+    }
+}
+
+impl fmt::Show for BitSetDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let dom = self.dom.borrow();
+        let mut s = format!("({}, {}) [", dom.min, dom.max);
+        for word in dom.words.iter() {
+            s = s + format!("{:016x} ", *word);
+        }
+        return write!(f, "{}]", s);
+    }
+}
+
+impl SparseDomain {
+    fn has(&self, val: int) -> bool {
+        if val < self.min.get() || val > self.max.get() { return false; }
+        let arrays = self.arrays.borrow();
+        *arrays.sparse.get((val - arrays.offset) as uint) < self.size.get()
+    }
+
+    /// removes a value which is present, leaving the bounds untouched
+    fn delete(&self, val: int) {
+        let mut arrays = self.arrays.borrow_mut();
+        let offset = arrays.offset;
+        let pos = *arrays.sparse.get((val - offset) as uint);
+        let last = self.size.get() - 1;
+        let other = *arrays.dense.get(last);
+        *arrays.dense.get_mut(pos) = other;
+        *arrays.sparse.get_mut((other - offset) as uint) = pos;
+        *arrays.dense.get_mut(last) = val;
+        *arrays.sparse.get_mut((val - offset) as uint) = last;
+        self.size.set(last);
+    }
+
+    /// removes the values not satisfying keep, leaving the bounds untouched
+    fn retain(&self, keep: |int| -> bool) {
+        let mut i = self.size.get();
+        while i > 0 {
+            i -= 1;
+            // values past i were kept or swapped past the end
+            let v = *self.arrays.borrow().dense.get(i);
+            if !keep(v) { self.delete(v); }
+        }
+    }
+
+    /// smallest value at least val, stepping through the range only when it
+    /// is narrower than the set
+    fn closest_above(&self, val: int) -> Option<int> {
+        if val > self.max.get() { return None; }
+        let mut v = max(val, self.min.get());
+        if ((self.max.get() - v) as uint) < self.size.get() {
+            while !self.has(v) {
+                v += 1;
+            }
+            Some(v)
+        } else {
+            let arrays = self.arrays.borrow();
+            arrays.dense.slice_to(self.size.get()).iter().map(|&v| v).filter(|&v| v >= val).min()
+        }
+    }
+
+    /// greatest value at most val, see closest_above
+    fn closest_below(&self, val: int) -> Option<int> {
+        if val < self.min.get() { return None; }
+        let mut v = min(val, self.max.get());
+        if ((v - self.min.get()) as uint) < self.size.get() {
+            while !self.has(v) {
+                v -= 1;
+            }
+            Some(v)
+        } else {
+            let arrays = self.arrays.borrow();
+            arrays.dense.slice_to(self.size.get()).iter().map(|&v| v).filter(|&v| v <= val).max()
+        }
+    }
+}
+
+impl Domain for SparseDomain {
+    fn new(min: int, max: int) -> SparseDomain {
+        let size = (max - min + 1) as uint;
+        SparseDomain {
+            arrays: Rc::new(RefCell::new(SparseArrays {
+                        offset: min,
+                        dense: range(min, max + 1).collect(),
+                        sparse: range(0, size).collect()
+                    })),
+            size: Cell::new(size),
+            min: Cell::new(min),
+            max: Cell::new(max)
+        }
+    }
+
+    fn restore(&self, saved: &SparseDomain) {
+        self.size.set(saved.size.get());
+        self.min.set(saved.min.get());
+        self.max.set(saved.max.get());
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
+        if min <= self.min.get() { return Ok(false); }
+        if min > self.max.get() { return Err(Failure); }
+        if ((min - self.min.get()) as uint) < self.size.get() {
+            for v in range(self.min.get(), min) {
+                if self.has(v) { self.delete(v); }
+            }
+        } else {
+            self.retain(|v| v >= min);
+        }
+        // the max is still there
+        self.min.set(self.closest_above(min).unwrap());
+        Ok(true)
+    }
+
+    fn get_min(&self) -> int {
+        self.min.get()
+    }
+
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
+        if max >= self.max.get() { return Ok(false); }
+        if max < self.min.get() { return Err(Failure); }
+        if ((self.max.get() - max) as uint) < self.size.get() {
+            for v in range(max + 1, self.max.get() + 1) {
+                if self.has(v) { self.delete(v); }
+            }
+        } else {
+            self.retain(|v| v <= max);
+        }
+        // the min is still there
+        self.max.set(self.closest_below(max).unwrap());
+        Ok(true)
+    }
+
+    fn get_max(&self) -> int {
+        self.max.get()
+    }
+
+    fn remove(&self, val: int) -> Result<bool, Failure> {
+        if val == self.min.get() && val == self.max.get() { return Err(Failure); }
+        if val == self.min.get() { return self.set_min(val + 1); }
+        if val == self.max.get() { return self.set_max(val - 1); }
+        if !self.has(val) { return Ok(false); }
+        self.delete(val);
+        Ok(true)
+    }
+
+    fn size(&self) -> uint {
+        self.size.get()
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        let mut values: Vec<int> = self.arrays.borrow().dense.slice_to(self.size.get()).to_vec();
+        values.sort();
+        let mut intervals = Vec::new();
+        let (mut start, mut end) = (*values.get(0), *values.get(0));
+        for &v in values.iter().skip(1) {
+            if v == end + 1 {
+                end = v;
+            } else {
+                intervals.push((start, end));
+                start = v;
+                end = v;
+            }
+        }
+        intervals.push((start, end));
+        intervals
+    }
+
+    fn contains(&self, val: int) -> bool {
+        self.has(val)
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        if val >= self.max.get() { return None; }
+        self.closest_above(val + 1)
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        if val <= self.min.get() { return None; }
+        self.closest_below(val - 1)
+    }
+}
+
+impl fmt::Show for SparseDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let arrays = self.arrays.borrow();
+        write!(f, "({}, {}) {}", self.min.get(), self.max.get(),
+               arrays.dense.slice_to(self.size.get()))
+    }
+}
+
+impl AdaptiveDomain {
+    /// switches to a bit vector representation if the range allows it,
+    /// unless there are few runs for the words they would take
+    fn adapt(&self) {
+        let packed = {
+            let dom = self.dom.borrow();
+            let (min, max) = (dom.get_min(), dom.get_max());
+            match *dom {
+                Intervals(_) | BitSet(_) if max - min < 64 => Some(Bits(pack(&*dom))),
+                Intervals(_) if max - min < ADAPTIVE_BITSET_RANGE
+                                && !few_runs(dom.intervals().len(), min, max) => {
+                    Some(BitSet(pack(&*dom)))
+                },
+                BitSet(ref d) if few_runs(d.count_runs(), min, max) => {
+                    Some(Intervals(pack(&*dom)))
+                },
+                _ => None
+            }
+        };
+        match packed {
+            Some(dom) => *self.dom.borrow_mut() = dom,
+            None => ()
+        }
+    }
+
+    /// keeps the representation up to date after a modification
+    fn adapting(&self, result: Result<bool, Failure>) -> Result<bool, Failure> {
+        if result == Ok(true) {
+            self.adapt();
+        }
+        result
+    }
+}
+
+/// whether runs of values between min and max are cheaper as a list of
+/// intervals than as words
+fn few_runs(runs: uint, min: int, max: int) -> bool {
+    runs * ADAPTIVE_WORDS_PER_RUN < ((max - min) as uint) / 64 + 1
+}
+
+/// copy of a domain in another representation
+fn pack<D: Domain>(from: &VarDomain) -> D {
+    let to: D = Domain::new(from.get_min(), from.get_max());
+    // the values of the domain are within the bounds, this cannot fail
+    let _ = to.intersect_with(from.intervals().as_slice());
+    to
+}
+
+impl Domain for AdaptiveDomain {
+    fn new(min: int, max: int) -> AdaptiveDomain {
+        let d = AdaptiveDomain { dom: RefCell::new(Intervals(Domain::new(min, max))) };
+        d.adapt();
+        d
+    }
+
+    fn restore(&self, saved: &AdaptiveDomain) {
+        *self.dom.borrow_mut() = saved.dom.borrow().clone();
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
+        let result = self.dom.borrow().set_min(min);
+        self.adapting(result)
+    }
+
+    fn get_min(&self) -> int {
+        self.dom.borrow().get_min()
+    }
+
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
+        let result = self.dom.borrow().set_max(max);
+        self.adapting(result)
+    }
+
+    fn get_max(&self) -> int {
+        self.dom.borrow().get_max()
+    }
+
+    fn remove(&self, val: int) -> Result<bool, Failure> {
+        let result = self.dom.borrow().remove(val);
+        self.adapting(result)
+    }
+
+    fn size(&self) -> uint {
+        self.dom.borrow().size()
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        self.dom.borrow().intervals()
+    }
+
+    fn remove_range(&self, lo: int, hi: int) -> Result<bool, Failure> {
+        let result = self.dom.borrow().remove_range(lo, hi);
+        self.adapting(result)
+    }
+
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<bool, Failure> {
+        let result = self.dom.borrow().intersect_with(intervals);
+        self.adapting(result)
+    }
+
+    fn contains(&self, val: int) -> bool {
+        self.dom.borrow().contains(val)
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        self.dom.borrow().next_value(val)
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        self.dom.borrow().prev_value(val)
+    }
+}
+
+impl fmt::Show for AdaptiveDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", *self.dom.borrow())
+    }
+}
+
+impl Domain for VarDomain {
+    /// the bit vector representation is used when it fits
+    fn new(min: int, max: int) -> VarDomain {
+        if max - min < 64 {
+            Bits(Domain::new(min, max))
+        } else {
+            Intervals(Domain::new(min, max))
+        }
+    }
+
+    fn restore(&self, saved: &VarDomain) {
+        match (self, saved) {
+            (&Intervals(ref d), &Intervals(ref s)) => d.restore(s),
+            (&Bits(ref d), &Bits(ref s)) => d.restore(s),
+            (&BitSet(ref d), &BitSet(ref s)) => d.restore(s),
+            (&Sparse(ref d), &Sparse(ref s)) => d.restore(s),
+            (&Adaptive(ref d), &Adaptive(ref s)) => d.restore(&**s),
+            _ => fail!("restoring a domain with a different representation")
+        }
+    }
+
+    fn set_min(&self, min: int) -> Result<bool, Failure> {
+        match *self {
+            Intervals(ref d) => d.set_min(min),
+            Bits(ref d) => d.set_min(min),
+            BitSet(ref d) => d.set_min(min),
+            Sparse(ref d) => d.set_min(min),
+            Adaptive(ref d) => d.set_min(min)
+        }
+    }
+
+    fn get_min(&self) -> int {
+        match *self {
+            Intervals(ref d) => d.get_min(),
+            Bits(ref d) => d.get_min(),
+            BitSet(ref d) => d.get_min(),
+            Sparse(ref d) => d.get_min(),
+            Adaptive(ref d) => d.get_min()
+        }
+    }
+
+    fn set_max(&self, max: int) -> Result<bool, Failure> {
+        match *self {
+            Intervals(ref d) => d.set_max(max),
+            Bits(ref d) => d.set_max(max),
+            BitSet(ref d) => d.set_max(max),
+            Sparse(ref d) => d.set_max(max),
+            Adaptive(ref d) => d.set_max(max)
+        }
+    }
+
+    fn get_max(&self) -> int {
+        match *self {
+            Intervals(ref d) => d.get_max(),
+            Bits(ref d) => d.get_max(),
+            BitSet(ref d) => d.get_max(),
+            Sparse(ref d) => d.get_max(),
+            Adaptive(ref d) => d.get_max()
+        }
+    }
+
+    fn remove(&self, val: int) -> Result<bool, Failure> {
+        match *self {
+            Intervals(ref d) => d.remove(val),
+            Bits(ref d) => d.remove(val),
+            BitSet(ref d) => d.remove(val),
+            Sparse(ref d) => d.remove(val),
+            Adaptive(ref d) => d.remove(val)
+        }
+    }
+
+    fn size(&self) -> uint {
+        match *self {
+            Intervals(ref d) => d.size(),
+            Bits(ref d) => d.size(),
+            BitSet(ref d) => d.size(),
+            Sparse(ref d) => d.size(),
+            Adaptive(ref d) => d.size()
+        }
+    }
+
+    fn intervals(&self) -> Vec<(int, int)> {
+        match *self {
+            Intervals(ref d) => d.intervals(),
+            Bits(ref d) => d.intervals(),
+            BitSet(ref d) => d.intervals(),
+            Sparse(ref d) => d.intervals(),
+            Adaptive(ref d) => d.intervals()
+        }
+    }
+
+    fn remove_range(&self, lo: int, hi: int) -> Result<bool, Failure> {
+        match *self {
+            Intervals(ref d) => d.remove_range(lo, hi),
+            Bits(ref d) => d.remove_range(lo, hi),
+            BitSet(ref d) => d.remove_range(lo, hi),
+            Sparse(ref d) => d.remove_range(lo, hi),
+            Adaptive(ref d) => d.remove_range(lo, hi)
+        }
+    }
+
+    fn intersect_with(&self, intervals: &[(int, int)]) -> Result<bool, Failure> {
+        match *self {
+            Intervals(ref d) => d.intersect_with(intervals),
+            Bits(ref d) => d.intersect_with(intervals),
+            BitSet(ref d) => d.intersect_with(intervals),
+            Sparse(ref d) => d.intersect_with(intervals),
+            Adaptive(ref d) => d.intersect_with(intervals)
+        }
+    }
+
+    fn contains(&self, val: int) -> bool {
+        match *self {
+            Intervals(ref d) => d.contains(val),
+            Bits(ref d) => d.contains(val),
+            BitSet(ref d) => d.contains(val),
+            Sparse(ref d) => d.contains(val),
+            Adaptive(ref d) => d.contains(val)
+        }
+    }
+
+    fn next_value(&self, val: int) -> Option<int> {
+        match *self {
+            Intervals(ref d) => d.next_value(val),
+            Bits(ref d) => d.next_value(val),
+            BitSet(ref d) => d.next_value(val),
+            Sparse(ref d) => d.next_value(val),
+            Adaptive(ref d) => d.next_value(val)
+        }
+    }
+
+    fn prev_value(&self, val: int) -> Option<int> {
+        match *self {
+            Intervals(ref d) => d.prev_value(val),
+            Bits(ref d) => d.prev_value(val),
+            BitSet(ref d) => d.prev_value(val),
+            Sparse(ref d) => d.prev_value(val),
+            Adaptive(ref d) => d.prev_value(val)
+        }
+    }
+}
+
+impl fmt::Show for VarDomain {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Intervals(ref d) => d.fmt(f),
+            Bits(ref d) => d.fmt(f),
+            BitSet(ref d) => d.fmt(f),
+            Sparse(ref d) => d.fmt(f),
+            Adaptive(ref d) => d.fmt(f)
+        }
     }
 }
 
 mod ltxy;
 mod eqxy;
+mod inxs;
+mod linear;
+mod alldiff;
+mod element;
+mod table;
+mod scheduling;
+mod cumulative;
+mod disjunctive;
+mod reified;
+mod search;
+mod heuristics;
 
 #[cfg(test)]
 mod tests;
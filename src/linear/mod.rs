@@ -0,0 +1,140 @@
+use super::{Event, Min, Ins, Prop, Mod, FDVar, Propagator, Failure};
+
+use std::rc::{Rc, Weak};
+
+/// how a linear expression compares to a constant
+#[deriving(Show, Clone, PartialEq, Eq)]
+pub enum Relation {
+    Equals,
+    LessOrEqual,
+    GreaterOrEqual,
+    Differs
+}
+
+/// x_1 + ... + x_n op C
+pub struct Sum;
+
+impl Sum {
+    pub fn new(model: Rc<Mod>, vars: &[Rc<FDVar>], rel: Relation, c: int) {
+        let terms: Vec<(int, Rc<FDVar>)> = vars.iter().map(|x| (1, x.clone())).collect();
+        Linear::new(model, terms.as_slice(), rel, c);
+    }
+}
+
+/// a_1 * x_1 + ... + a_n * x_n op C
+pub struct Linear;
+
+impl Linear {
+    pub fn new(model: Rc<Mod>, terms: &[(int, Rc<FDVar>)], rel: Relation, c: int) {
+        // terms are turned into views, so that propagators only add
+        let vars: Vec<Rc<FDVar>> = terms.iter()
+            .filter(|&&(a, _)| a != 0)
+            .map(|&(a, ref x)| if a == 1 { x.clone() } else { x.scale(a) })
+            .collect();
+        match rel {
+            Equals => {
+                SumLe::new(model.clone(), vars.clone(), c);
+                SumLe::new(model, negated(vars.as_slice()), -c);
+            },
+            LessOrEqual => SumLe::new(model, vars, c),
+            GreaterOrEqual => SumLe::new(model, negated(vars.as_slice()), -c),
+            Differs => SumNe::new(model, vars, c)
+        }
+    }
+}
+
+fn negated(vars: &[Rc<FDVar>]) -> Vec<Rc<FDVar>> {
+    vars.iter().map(|x| x.neg()).collect()
+}
+
+/// x_1 + ... + x_n <= c, bounds consistent
+struct SumLe : Prop {
+    c: int
+}
+
+impl SumLe {
+    fn new(model: Rc<Mod>, vars: Vec<Rc<FDVar>>, c: int) {
+        let id = model.propagators.borrow().len();
+        let this = SumLe { model: model.downgrade(), id: id, vars: vars, c: c};
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+}
+
+impl Propagator for SumLe {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        self.vars.iter().map(|x| (x.id, Min)).collect()
+    }
+
+    fn priority(&self) -> uint {
+        1
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let min = self.vars.iter().fold(0, |sum, x| sum + x.min());
+        let max = self.vars.iter().fold(0, |sum, x| sum + x.max());
+        if min > self.c { return Err(Failure); }
+        if max <= self.c {
+            self.entail();
+            return Ok(vec![]);
+        }
+        // the minimum of the others does not change while x_i is bounded
+        let mut woken = Vec::new();
+        for x in self.vars.iter() {
+            woken.push_all(try!(x.set_max(self.c - min + x.min())).as_slice());
+        }
+        Ok(woken)
+    }
+}
+
+/// x_1 + ... + x_n != c, pruning once all the variables but one are fixed
+struct SumNe : Prop {
+    c: int
+}
+
+impl SumNe {
+    fn new(model: Rc<Mod>, vars: Vec<Rc<FDVar>>, c: int) {
+        let id = model.propagators.borrow().len();
+        let this = SumNe { model: model.downgrade(), id: id, vars: vars, c: c};
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+}
+
+impl Propagator for SumNe {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        self.vars.iter().map(|x| (x.id, Ins)).collect()
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let mut free = self.vars.iter().filter(|x| !x.is_fixed());
+        let first = free.next();
+        if first.is_some() && free.next().is_some() { return Ok(vec![]); }
+        let fixed = self.vars.iter().filter(|x| x.is_fixed()).fold(0, |sum, x| sum + x.min());
+        self.entail();
+        match first {
+            Some(x) => x.remove(self.c - fixed),
+            None if fixed == self.c => Err(Failure),
+            None => Ok(vec![])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,84 @@
+use super::super::{Model, Var, Solution, LeXC, GeXC, NeqXC, Sum, Linear, Equals, LessOrEqual, GreaterOrEqual, Differs};
+
+#[test]
+fn sum_at_most_bounds() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 2, 10, "y");
+    let z = Var::new(m.clone(), 1, 10, "z");
+    Sum::new(m.clone(), &[x.clone(), y.clone(), z.clone()], LessOrEqual, 8);
+    assert_eq!((x.max(), y.max(), z.max()), (5, 7, 6));
+    GeXC::new(m.clone(), y.clone(), 5);
+    assert_eq!((x.max(), y.max(), z.max()), (2, 7, 3));
+    LeXC::new(m.clone(), x.clone(), 3);
+    GeXC::new(m.clone(), z.clone(), 3);
+    assert_eq!((x.max(), y.max(), z.max()), (0, 5, 3));
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn sum_equals_both_ways() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 5, "x");
+    let y = Var::new(m.clone(), 0, 5, "y");
+    Sum::new(m.clone(), &[x.clone(), y.clone()], Equals, 8);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (3, 5, 3, 5));
+    LeXC::new(m.clone(), x.clone(), 4);
+    assert_eq!((y.min(), y.max()), (4, 5));
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone()]), 2);
+}
+
+#[test]
+fn linear_weighted_terms() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    // 3x - 2y >= 10
+    Linear::new(m.clone(), &[(3, x.clone()), (-2, y.clone())], GreaterOrEqual, 10);
+    assert_eq!((x.min(), y.max()), (4, 10));
+    LeXC::new(m.clone(), x.clone(), 6);
+    assert_eq!(y.max(), 4);
+    let solutions = m.solutions(vec![x.clone(), y.clone()]).collect::<Vec<Solution>>();
+    for s in solutions.iter() {
+        assert!(3 * *s.get(&"x".to_string()) - 2 * *s.get(&"y".to_string()) >= 10);
+    }
+    assert_eq!(solutions.len(), 2 + 3 + 5);
+}
+
+#[test]
+fn linear_ignores_null_coefficients() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    Linear::new(m.clone(), &[(2, x.clone()), (0, y.clone())], Equals, 6);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (3, 3, 0, 10));
+}
+
+#[test]
+fn sum_differs_when_one_left() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let z = Var::new(m.clone(), 0, 10, "z");
+    Linear::new(m.clone(), &[(1, x.clone()), (2, y.clone()), (1, z.clone())], Differs, 9);
+    GeXC::new(m.clone(), x.clone(), 3);
+    LeXC::new(m.clone(), x.clone(), 3);
+    assert_eq!(z.size(), 11);
+    GeXC::new(m.clone(), y.clone(), 2);
+    LeXC::new(m.clone(), y.clone(), 2);
+    assert!(!z.contains(2));
+    assert_eq!(z.size(), 10);
+}
+
+#[test]
+fn sum_fails_when_infeasible() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 3, "x");
+    let y = Var::new(m.clone(), 0, 3, "y");
+    Sum::new(m.clone(), &[x.clone(), y.clone()], GreaterOrEqual, 5);
+    assert!(!m.is_failed());
+    NeqXC::new(m.clone(), x.clone(), 3);
+    assert_eq!(y.min(), 3);
+    LeXC::new(m.clone(), y.clone(), 2);
+    assert!(m.is_failed());
+}
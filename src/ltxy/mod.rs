@@ -1,4 +1,4 @@
-use super::{Event, Max, Min, Prop, Mod, FDVar, Propagator};
+use super::{Event, Max, Min, Prop, Mod, FDVar, Propagator, Failure};
 
 use std::rc::{Rc, Weak};
 
@@ -26,7 +26,7 @@ pub struct LtXC;
 
 impl LtXC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, c: int) {
-        model.propagate_vec(x.set_max(c - 1));
+        model.post(x.set_max(c - 1));
     }
 }
 
@@ -53,7 +53,7 @@ pub struct LeXC;
 
 impl LeXC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, c: int) {
-        model.propagate_vec(x.set_max(c));
+        model.post(x.set_max(c));
     }
 }
 
@@ -80,7 +80,7 @@ pub struct GtXC;
 
 impl GtXC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, c: int) {
-        model.propagate_vec(x.set_min(c + 1));
+        model.post(x.set_min(c + 1));
     }
 }
 
@@ -107,10 +107,20 @@ pub struct GeXC;
 
 impl GeXC {
     pub fn new(model: Rc<Mod>, x: Rc<FDVar>, c: int) {
-        model.propagate_vec(x.set_min(c));
+        model.post(x.set_min(c));
     }
 }
 
+/// x < y + c, bounds the max of x
+pub fn filter_lt_x(x: &FDVar, y: &FDVar, c: int) -> Result<Vec<uint>, Failure> {
+    x.set_max(y.max() + c - 1)
+}
+
+/// x < y + c, bounds the min of y
+pub fn filter_lt_y(x: &FDVar, y: &FDVar, c: int) -> Result<Vec<uint>, Failure> {
+    y.set_min(x.min() - c + 1)
+}
+
 struct LtXYCx : Prop {
     c: int
 }
@@ -144,19 +154,13 @@ impl Propagator for LtXYCx {
         vec![(self.y().id, Max)]
     }
 
-    fn propagate(&self) -> Vec<uint> {
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
         if self.x().max() < self.y().min() + self.c {
             // entailed
-            self.unregister();
-            vec![]
-        } else if self.x().max() > self.y().max() + self.c - 1 {
-            //if y.is_instanciated() {
-            //   self.unregister();
-            //}
-            let max = self.y().max() + self.c - 1;
-            self.x().set_max(max)
+            self.entail();
+            Ok(vec![])
         } else {
-            vec![]
+            filter_lt_x(&*self.x(), &*self.y(), self.c)
         }
     }
 }
@@ -194,19 +198,13 @@ impl Propagator for LtXYCy {
         vec![(self.x().id, Min)]
     }
 
-    fn propagate(&self) -> Vec<uint> {
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
         if self.x().max() < self.y().min() + self.c {
             // entailed
-            self.unregister();
-            vec![]
-        } else if self.y().min() < self.x().min() - self.c + 1 {
-            //if y.is_instanciated() {
-            //   self.unregister();
-            //}
-            let min = self.x().min() - self.c + 1;
-            self.y().set_min(min)
+            self.entail();
+            Ok(vec![])
         } else {
-            vec![]
+            filter_lt_y(&*self.x(), &*self.y(), self.c)
         }
     }
 }
@@ -1,6 +1,8 @@
-use super::super::{Model, Var, LtXY, GtXY, LeXY, GeXY, LtXYC, GtXYC, LeXYC, GeXYC, LtXC, GtXC, LeXC, GeXC};
+use super::super::{Model, Var, FDVar, Min, LtXY, GtXY, LeXY, GeXY, LtXYC, GtXYC, LeXYC, GeXYC, LtXC, GtXC, LeXC, GeXC};
 use super::{LtXYCx, LtXYCy};
 
+use std::rc::Rc;
+
 #[test]
 fn propagator_does_propagate() {
     let m = Model::new();
@@ -115,3 +117,53 @@ fn gexc_does_propagate() {
     GeXC::new(m.clone(), x.clone(), 3);
     assert_eq!((x.min(), x.max()), (3, 252));
 }
+
+#[test]
+fn inconsistent_model_fails() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), -2, 255, "x");
+    let y = Var::new(m.clone(), -2, 255, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    assert!(!m.is_failed());
+    LtXY::new(m.clone(), y.clone(), x.clone());
+    assert!(m.is_failed());
+}
+
+#[test]
+fn unary_constraint_fails() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), -2, 252, "x");
+    GtXC::new(m.clone(), x.clone(), 3);
+    LtXC::new(m.clone(), x.clone(), 4);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn entailed_chain_still_propagates() {
+    let m = Model::new();
+    let x: Vec<Rc<FDVar>> = range(0, 5)
+        .map(|i| Var::new(m.clone(), 0, 100, format!("x{}", i).as_slice()))
+        .collect();
+    for i in range(1, x.len()) {
+        LtXY::new(m.clone(), x.get(i - 1).clone(), x.get(i).clone());
+    }
+    m.push_level();
+    LeXC::new(m.clone(), x.get(2).clone(), 50);
+    GeXC::new(m.clone(), x.get(2).clone(), 50);
+    GeXC::new(m.clone(), x.get(0).clone(), 10);
+    assert!(m.is_entailed(3));
+    assert!(m.get_waiting(x.get(1).id, Min).is_empty());
+    LeXC::new(m.clone(), x.get(4).clone(), 60);
+    GeXC::new(m.clone(), x.get(3).clone(), 55);
+    assert!(m.is_entailed(4) && !m.is_entailed(5));
+    assert_eq!((x.get(3).max(), x.get(4).min()), (59, 56));
+    LeXC::new(m.clone(), x.get(1).clone(), 20);
+    assert_eq!((x.get(0).min(), x.get(0).max()), (10, 19));
+    m.pop_level();
+    assert!(!m.is_entailed(3) && !m.is_entailed(4));
+    assert_eq!(m.get_waiting(x.get(1).id, Min), vec![3]);
+    LeXC::new(m.clone(), x.get(4).clone(), 10);
+    GeXC::new(m.clone(), x.get(0).clone(), 3);
+    let bounds: Vec<(int, int)> = x.iter().map(|v| (v.min(), v.max())).collect();
+    assert_eq!(bounds, vec![(3, 6), (4, 7), (5, 8), (6, 9), (7, 10)]);
+}
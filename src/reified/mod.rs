@@ -0,0 +1,274 @@
+use super::{Event, Dom, Prop, Mod, FDVar, Propagator, Failure};
+use super::ltxy::{filter_lt_x, filter_lt_y};
+use super::eqxy::{filter_eq, filter_neq};
+
+use std::rc::{Rc, Weak};
+
+/// B <=> X < Y + C, with B a 0/1 variable
+pub struct ReifLtXYC;
+
+impl ReifLtXYC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, LessXYC(x, y, c), false);
+    }
+
+    /// B => X < Y + C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, LessXYC(x, y, c), true);
+    }
+}
+
+/// B <=> X =< Y + C
+pub struct ReifLeXYC;
+
+impl ReifLeXYC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, LessXYC(x, y, c + 1), false);
+    }
+
+    /// B => X =< Y + C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, LessXYC(x, y, c + 1), true);
+    }
+}
+
+/// B <=> X = Y + C
+pub struct ReifEqXYC;
+
+impl ReifEqXYC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, EqualXYC(x, y, c), false);
+    }
+
+    /// B => X = Y + C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, EqualXYC(x, y, c), true);
+    }
+}
+
+/// B <=> X != Y + C
+pub struct ReifNeqXYC;
+
+impl ReifNeqXYC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, NotEqualXYC(x, y, c), false);
+    }
+
+    /// B => X != Y + C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, y: Rc<FDVar>, c: int) {
+        Reified::new(model, b, NotEqualXYC(x, y, c), true);
+    }
+}
+
+/// B <=> X =< C
+pub struct ReifLeXC;
+
+impl ReifLeXC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, AtMostXC(x, c), false);
+    }
+
+    /// B => X =< C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, AtMostXC(x, c), true);
+    }
+}
+
+/// B <=> X >= C
+pub struct ReifGeXC;
+
+impl ReifGeXC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, AtLeastXC(x, c), false);
+    }
+
+    /// B => X >= C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, AtLeastXC(x, c), true);
+    }
+}
+
+/// B <=> X = C
+pub struct ReifEqXC;
+
+impl ReifEqXC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, EqualXC(x, c), false);
+    }
+
+    /// B => X = C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, EqualXC(x, c), true);
+    }
+}
+
+/// B <=> X != C
+pub struct ReifNeqXC;
+
+impl ReifNeqXC {
+    pub fn new(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, NotEqualXC(x, c), false);
+    }
+
+    /// B => X != C
+    pub fn implied(model: Rc<Mod>, b: Rc<FDVar>, x: Rc<FDVar>, c: int) {
+        Reified::new(model, b, NotEqualXC(x, c), true);
+    }
+}
+
+/// constraints that can be reified, with their negation among them
+#[deriving(Clone)]
+enum Condition {
+    /// X < Y + C
+    LessXYC(Rc<FDVar>, Rc<FDVar>, int),
+    /// X = Y + C
+    EqualXYC(Rc<FDVar>, Rc<FDVar>, int),
+    /// X != Y + C
+    NotEqualXYC(Rc<FDVar>, Rc<FDVar>, int),
+    /// X =< C
+    AtMostXC(Rc<FDVar>, int),
+    /// X >= C
+    AtLeastXC(Rc<FDVar>, int),
+    /// X = C
+    EqualXC(Rc<FDVar>, int),
+    /// X != C
+    NotEqualXC(Rc<FDVar>, int)
+}
+
+impl Condition {
+    fn vars(&self) -> Vec<Rc<FDVar>> {
+        match *self {
+            LessXYC(ref x, ref y, _) | EqualXYC(ref x, ref y, _)
+                | NotEqualXYC(ref x, ref y, _) => vec![x.clone(), y.clone()],
+            AtMostXC(ref x, _) | AtLeastXC(ref x, _) | EqualXC(ref x, _)
+                | NotEqualXC(ref x, _) => vec![x.clone()]
+        }
+    }
+
+    fn negate(&self) -> Condition {
+        match *self {
+            // X >= Y + C is Y < X - C + 1
+            LessXYC(ref x, ref y, c) => LessXYC(y.clone(), x.clone(), 1 - c),
+            EqualXYC(ref x, ref y, c) => NotEqualXYC(x.clone(), y.clone(), c),
+            NotEqualXYC(ref x, ref y, c) => EqualXYC(x.clone(), y.clone(), c),
+            AtMostXC(ref x, c) => AtLeastXC(x.clone(), c + 1),
+            AtLeastXC(ref x, c) => AtMostXC(x.clone(), c - 1),
+            EqualXC(ref x, c) => NotEqualXC(x.clone(), c),
+            NotEqualXC(ref x, c) => EqualXC(x.clone(), c)
+        }
+    }
+
+    /// holds for any values left in the domains, only checking the bounds
+    /// and instanciated variables
+    fn is_entailed(&self) -> bool {
+        match *self {
+            LessXYC(ref x, ref y, c) => x.max() < y.min() + c,
+            EqualXYC(ref x, ref y, c) => {
+                x.is_instanciated() && y.is_instanciated() && x.min() == y.min() + c
+            },
+            NotEqualXYC(ref x, ref y, c) => {
+                x.max() < y.min() + c || x.min() > y.max() + c
+                    || (x.is_instanciated() && !y.contains(x.min() - c))
+                    || (y.is_instanciated() && !x.contains(y.min() + c))
+            },
+            AtMostXC(ref x, c) => x.max() <= c,
+            AtLeastXC(ref x, c) => x.min() >= c,
+            EqualXC(ref x, c) => x.is_instanciated() && x.min() == c,
+            NotEqualXC(ref x, c) => !x.contains(c)
+        }
+    }
+
+    /// removes the values violating the condition
+    fn enforce(&self) -> Result<Vec<uint>, Failure> {
+        match *self {
+            LessXYC(ref x, ref y, c) => {
+                let mut woken = try!(filter_lt_x(&**x, &**y, c));
+                woken.push_all(try!(filter_lt_y(&**x, &**y, c)).as_slice());
+                Ok(woken)
+            },
+            EqualXYC(ref x, ref y, c) => filter_eq(&**x, &**y, c),
+            NotEqualXYC(ref x, ref y, c) => filter_neq(&**x, &**y, c),
+            AtMostXC(ref x, c) => x.set_max(c),
+            AtLeastXC(ref x, c) => x.set_min(c),
+            EqualXC(ref x, c) => {
+                let mut woken = try!(x.set_min(c));
+                woken.push_all(try!(x.set_max(c)).as_slice());
+                Ok(woken)
+            },
+            NotEqualXC(ref x, c) => x.remove(c)
+        }
+    }
+}
+
+/// b <=> condition, or b => condition for half-reification
+struct Reified : Prop {
+    condition: Condition,
+    half: bool
+}
+
+impl Reified {
+    fn new(model: Rc<Mod>, b: Rc<FDVar>, condition: Condition, half: bool) {
+        model.post(b.intersect_with(&[(0, 1)]));
+        let id = model.propagators.borrow().len();
+        let mut vars = vec![b];
+        vars.push_all(condition.vars().as_slice());
+        let this = Reified {
+            model: model.downgrade(),
+            id: id,
+            vars: vars,
+            condition: condition,
+            half: half
+        };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    fn b(&self) -> Rc<FDVar> {
+        self.vars.get(0).clone()
+    }
+}
+
+impl Propagator for Reified {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        self.vars.iter().map(|x| (x.id, Dom)).collect()
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let b = self.b();
+        if b.is_instanciated() {
+            let condition = match b.min() {
+                1 => self.condition.clone(),
+                _ if self.half => {
+                    // nothing is required when b is false
+                    self.entail();
+                    return Ok(vec![]);
+                },
+                _ => self.condition.negate()
+            };
+            let woken = try!(condition.enforce());
+            if condition.is_entailed() {
+                self.entail();
+            }
+            Ok(woken)
+        } else if self.condition.negate().is_entailed() {
+            self.entail();
+            b.set_max(0)
+        } else if self.condition.is_entailed() {
+            self.entail();
+            if self.half { Ok(vec![]) } else { b.set_min(1) }
+        } else {
+            Ok(vec![])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,94 @@
+use super::super::{Model, Var, EqXC, NeqXC, ReifLtXYC, ReifLeXYC, ReifEqXYC, ReifNeqXYC, ReifLeXC,
+    ReifGeXC, ReifEqXC, ReifNeqXC};
+
+#[test]
+fn bool_vars_are_0_1() {
+    let m = Model::new();
+    let b = Var::new_bool(m.clone(), "b");
+    assert_eq!((b.min(), b.max()), (0, 1));
+    let c = Var::new(m.clone(), -3, 5, "c");
+    let x = Var::new(m.clone(), 0, 5, "x");
+    ReifLeXC::new(m.clone(), c.clone(), x, 2);
+    assert_eq!((c.min(), c.max()), (0, 1));
+}
+
+#[test]
+fn conditions_fix_b() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 2, "x");
+    let y = Var::new(m.clone(), 5, 6, "y");
+    let bs = Vec::from_fn(4, |i| Var::new_bool(m.clone(), format!("b{}", i).as_slice()));
+    ReifLtXYC::new(m.clone(), bs.get(0).clone(), x.clone(), y.clone(), 0);
+    ReifEqXYC::new(m.clone(), bs.get(1).clone(), x.clone(), y.clone(), 0);
+    ReifEqXC::new(m.clone(), bs.get(2).clone(), x.clone(), 7);
+    ReifNeqXC::new(m.clone(), bs.get(3).clone(), y.clone(), 7);
+    assert_eq!(bs.iter().map(|b| (b.min(), b.max())).collect::<Vec<(int, int)>>(),
+               vec![(1, 1), (0, 0), (0, 0), (1, 1)]);
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn b_enforces_condition_or_negation() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let b = Var::new_bool(m.clone(), "b");
+    let c = Var::new_bool(m.clone(), "c");
+    ReifLeXYC::new(m.clone(), b.clone(), x.clone(), y.clone(), -3);
+    ReifGeXC::new(m.clone(), c.clone(), x.clone(), 2);
+    EqXC::new(m.clone(), b.clone(), 1);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (0, 7, 3, 10));
+    EqXC::new(m.clone(), c.clone(), 0);
+    assert_eq!((x.min(), x.max()), (0, 1));
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn reified_equality_shares_holes() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    let b = Var::new_bool(m.clone(), "b");
+    ReifEqXYC::new(m.clone(), b.clone(), x.clone(), y.clone(), 1);
+    NeqXC::new(m.clone(), y.clone(), 4);
+    EqXC::new(m.clone(), b.clone(), 1);
+    assert!(!x.contains(5) && !x.contains(0) && !y.contains(10));
+}
+
+#[test]
+fn counts_reified_solutions() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 2, "x");
+    let y = Var::new(m.clone(), 0, 2, "y");
+    let b = Var::new_bool(m.clone(), "b");
+    ReifLtXYC::new(m.clone(), b.clone(), x.clone(), y.clone(), 0);
+    for solution in m.solutions(vec![b.clone(), x.clone(), y.clone()]) {
+        let (b, x, y) = (*solution.get(&"b".to_string()), *solution.get(&"x".to_string()),
+                         *solution.get(&"y".to_string()));
+        assert_eq!(b == 1, x < y);
+    }
+    assert_eq!(m.count_solutions(vec![b.clone(), x.clone(), y.clone()]), 9);
+    let c = Var::new_bool(m.clone(), "c");
+    ReifNeqXYC::new(m.clone(), c.clone(), x.clone(), y.clone(), 0);
+    assert_eq!(m.count_solutions(vec![c.clone(), x.clone(), y.clone()]), 9);
+}
+
+#[test]
+fn half_reification_only_implies() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 4, "x");
+    let b = Var::new_bool(m.clone(), "b");
+    ReifEqXC::implied(m.clone(), b.clone(), x.clone(), 3);
+    // b false allows any value, b true only 3
+    assert_eq!(m.count_solutions(vec![b.clone(), x.clone()]), 5 + 1);
+    EqXC::new(m.clone(), b.clone(), 0);
+    assert_eq!((x.min(), x.max()), (0, 4));
+    let m = Model::new();
+    let x = Var::new(m.clone(), 5, 6, "x");
+    let y = Var::new(m.clone(), 0, 2, "y");
+    let b = Var::new_bool(m.clone(), "b");
+    ReifLtXYC::implied(m.clone(), b.clone(), x.clone(), y.clone(), 0);
+    assert_eq!((b.min(), b.max()), (0, 0));
+    ReifGeXC::implied(m.clone(), Var::new_bool(m.clone(), "c"), x.clone(), 0);
+    assert!(!m.is_failed());
+}
@@ -0,0 +1,52 @@
+use super::FDVar;
+
+/// a task as seen from the bounds of its start
+#[deriving(Clone)]
+pub struct Task {
+    /// index in the variables of the propagator
+    pub index: uint,
+    /// earliest start time
+    pub est: int,
+    /// latest completion time
+    pub lct: int,
+    pub duration: int,
+    pub demand: int
+}
+
+impl Task {
+    /// task i starting at x
+    pub fn new(index: uint, x: &FDVar, duration: int, demand: int) -> Task {
+        Task {
+            index: index,
+            est: x.min(),
+            lct: x.max() + duration,
+            duration: duration,
+            demand: demand
+        }
+    }
+
+    pub fn energy(&self) -> int {
+        self.duration * self.demand
+    }
+
+    /// latest start time
+    pub fn lst(&self) -> int {
+        self.lct - self.duration
+    }
+
+    /// earliest completion time
+    pub fn ect(&self) -> int {
+        self.est + self.duration
+    }
+
+    /// the same task when time goes backwards
+    pub fn mirror(&self) -> Task {
+        Task { est: -self.lct, lct: -self.est, .. self.clone() }
+    }
+}
+
+/// task with its bounds, for tests
+#[cfg(test)]
+pub fn task(est: int, lct: int, duration: int, demand: int) -> Task {
+    Task { index: 0, est: est, lct: lct, duration: duration, demand: demand }
+}
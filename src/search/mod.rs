@@ -0,0 +1,277 @@
+use super::{Mod, FDVar, Failure};
+use super::heuristics::{VarSelector, InputOrder, Brancher, AssignValue, MinValue};
+
+use std::collections::hashmap::HashMap;
+use std::rc::Rc;
+
+/// values of the labelled variables, by name, hence names must be distinct
+pub type Solution = HashMap<String, int>;
+
+/// Depth-first search labelling a list of variables, iterating over its
+/// solutions resumes the search after each of them
+pub struct Search {
+    model: Rc<Mod>,
+    vars: Vec<Rc<FDVar>>,
+    var_selector: Box<VarSelector>,
+    brancher: Box<Brancher>,
+    /// alternatives still to explore, one per open choice point
+    alternatives: Vec<Decision>,
+    objective: Option<Objective>,
+    /// constraint on the objective for solutions to improve on the last one
+    bound: Option<Decision>,
+    started: bool,
+    exhausted: bool,
+    /// level of the model before the search, restored when dropping it
+    root: uint
+}
+
+/// Direction of optimisation
+enum Objective {
+    Minimize(Rc<FDVar>),
+    Maximize(Rc<FDVar>)
+}
+
+/// Branching decisions, with their alternative for the right branch
+pub enum Decision {
+    /// X = V, alternative X != V
+    Assign(Rc<FDVar>, int),
+    /// X != V
+    Remove(Rc<FDVar>, int),
+    /// X =< V, alternative X > V
+    AtMost(Rc<FDVar>, int),
+    /// X >= V, alternative X < V
+    AtLeast(Rc<FDVar>, int)
+}
+
+impl Search {
+    pub fn new(model: Rc<Mod>, vars: Vec<Rc<FDVar>>) -> Search {
+        for (i, x) in vars.iter().enumerate() {
+            assert_unique_name(vars.slice_to(i), x);
+        }
+        let root = model.level();
+        Search {
+            model: model,
+            vars: vars,
+            var_selector: box InputOrder as Box<VarSelector>,
+            brancher: box AssignValue::new(MinValue) as Box<Brancher>,
+            alternatives: Vec::new(),
+            objective: None,
+            bound: None,
+            started: false,
+            exhausted: false,
+            root: root
+        }
+    }
+
+    /// variable ordering, input order by default
+    pub fn select_var(mut self, selector: Box<VarSelector>) -> Search {
+        self.var_selector = selector;
+        self
+    }
+
+    /// branching on the selected variable, x = min / x != min by default
+    pub fn branch_with(mut self, brancher: Box<Brancher>) -> Search {
+        self.brancher = brancher;
+        self
+    }
+
+    /// branch and bound: each solution found is strictly better than the
+    /// previous one, the last one is optimal if proved_optimal() holds
+    /// once the iteration is over
+    pub fn minimize(mut self, obj: Rc<FDVar>) -> Search {
+        self.label_too(obj.clone());
+        self.objective = Some(Minimize(obj));
+        self
+    }
+
+    /// see minimize
+    pub fn maximize(mut self, obj: Rc<FDVar>) -> Search {
+        self.label_too(obj.clone());
+        self.objective = Some(Maximize(obj));
+        self
+    }
+
+    /// the search space was fully explored with an objective, hence the last
+    /// solution found, if any, is optimal
+    pub fn proved_optimal(&self) -> bool {
+        self.exhausted && self.objective.is_some()
+    }
+
+    /// first solution found, the model stays in the corresponding state
+    /// until the search is dropped
+    pub fn solve(&mut self) -> Option<Solution> {
+        self.next()
+    }
+
+    /// number of remaining solutions, without building them
+    pub fn count_solutions(&mut self) -> uint {
+        let mut count = 0;
+        while self.next_leaf() {
+            count += 1;
+        }
+        count
+    }
+
+    /// goes down to the next solution, returns false when there is none
+    fn next_leaf(&mut self) -> bool {
+        if self.started {
+            if !self.backtrack() { return false; }
+        } else {
+            self.started = true;
+            // so that alternatives of the first choice point are undone too
+            self.model.push_level();
+            if self.model.is_failed() {
+                self.finish();
+                return false;
+            }
+        }
+        loop {
+            match self.select() {
+                None => {
+                    self.improve();
+                    return true;
+                },
+                Some(x) => {
+                    let left = self.brancher.branch(&x);
+                    self.model.push_level();
+                    self.alternatives.push(left.alternative());
+                    if left.apply(&*self.model).is_err() && !self.backtrack() {
+                        return false;
+                    }
+                }
+            }
+        }
+    }
+
+    /// undoes choice points until one of their alternatives does not fail,
+    /// returns false when the search space is exhausted
+    fn backtrack(&mut self) -> bool {
+        loop {
+            match self.alternatives.pop() {
+                None => {
+                    self.finish();
+                    return false;
+                },
+                Some(alternative) => {
+                    self.model.pop_level();
+                    if self.tighten().is_ok() && alternative.apply(&*self.model).is_ok() {
+                        return true;
+                    }
+                }
+            }
+        }
+    }
+
+    fn finish(&mut self) {
+        if !self.exhausted {
+            self.exhausted = true;
+            self.model.pop_level();
+        }
+    }
+
+    /// records the objective value of the current solution
+    fn improve(&mut self) {
+        self.bound = match self.objective {
+            Some(Minimize(ref obj)) => Some(AtMost(obj.clone(), obj.min() - 1)),
+            Some(Maximize(ref obj)) => Some(AtLeast(obj.clone(), obj.max() + 1)),
+            None => None
+        };
+    }
+
+    /// posts the bound on the objective, as LtXC or GtXC would, it is undone
+    /// with the level it is posted in and thus posted again after each
+    /// backtrack
+    fn tighten(&self) -> Result<(), Failure> {
+        match self.bound {
+            Some(ref bound) => bound.apply(&*self.model),
+            None => Ok(())
+        }
+    }
+
+    /// the objective has to be instanciated in solutions
+    fn label_too(&mut self, obj: Rc<FDVar>) {
+        if !self.vars.iter().any(|x| x.id == obj.id) {
+            assert_unique_name(self.vars.as_slice(), &obj);
+            self.vars.push(obj);
+        }
+    }
+
+    fn select(&mut self) -> Option<Rc<FDVar>> {
+        let candidates: Vec<Rc<FDVar>> = self.vars.iter()
+            .filter(|x| !x.is_instanciated())
+            .map(|x| x.clone())
+            .collect();
+        if candidates.is_empty() {
+            None
+        } else {
+            let i = self.var_selector.select(&*self.model, candidates.as_slice());
+            Some(candidates.get(i).clone())
+        }
+    }
+
+    fn solution(&self) -> Solution {
+        self.vars.iter().map(|x| (x.name.clone(), x.min())).collect()
+    }
+}
+
+/// solutions are keyed by name, another variable with the name of x would
+/// silently replace its value
+fn assert_unique_name(others: &[Rc<FDVar>], x: &Rc<FDVar>) {
+    assert!(!others.iter().any(|y| y.id != x.id && y.name == x.name),
+            "labelled variables must have distinct names, {} is not", x.name);
+}
+
+impl Iterator<Solution> for Search {
+    fn next(&mut self) -> Option<Solution> {
+        if self.next_leaf() {
+            Some(self.solution())
+        } else {
+            None
+        }
+    }
+}
+
+impl Drop for Search {
+    fn drop(&mut self) {
+        while self.model.level() > self.root {
+            self.model.pop_level();
+        }
+    }
+}
+
+impl Decision {
+    fn alternative(&self) -> Decision {
+        match *self {
+            Assign(ref x, v) => Remove(x.clone(), v),
+            Remove(ref x, v) => Assign(x.clone(), v),
+            AtMost(ref x, v) => AtLeast(x.clone(), v + 1),
+            AtLeast(ref x, v) => AtMost(x.clone(), v - 1)
+        }
+    }
+
+    fn apply(&self, model: &Mod) -> Result<(), Failure> {
+        match self.prune() {
+            Ok(woken) => model.propagate_vec(woken),
+            Err(failure) => {
+                model.fail();
+                Err(failure)
+            }
+        }
+    }
+
+    fn prune(&self) -> Result<Vec<uint>, Failure> {
+        match *self {
+            Assign(ref x, v) => {
+                let mut woken = try!(x.set_min(v));
+                woken.push_all(try!(x.set_max(v)).as_slice());
+                Ok(woken)
+            },
+            Remove(ref x, v) => x.remove(v),
+            AtMost(ref x, v) => x.set_max(v),
+            AtLeast(ref x, v) => x.set_min(v)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests;
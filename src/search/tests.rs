@@ -0,0 +1,136 @@
+use super::super::{Model, Var, LtXY, NeqXY, NeqXYC, EqXYC, GeXYC};
+use super::Search;
+
+#[test]
+fn finds_first_solution() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 2, "x");
+    let y = Var::new(m.clone(), 0, 2, "y");
+    let z = Var::new(m.clone(), 0, 2, "z");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    LtXY::new(m.clone(), y.clone(), z.clone());
+    let mut search = Search::new(m.clone(), vec![x.clone(), y.clone(), z.clone()]);
+    let sol = search.solve().unwrap();
+    assert_eq!((sol.get(&"x".to_string()), sol.get(&"y".to_string()), sol.get(&"z".to_string())),
+               (&0, &1, &2));
+}
+
+#[test]
+fn backtracks_on_failure() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 1, "x");
+    let y = Var::new(m.clone(), 0, 1, "y");
+    // x = 0 leaves no value for y
+    NeqXY::new(m.clone(), y.clone(), x.clone());
+    NeqXYC::new(m.clone(), y.clone(), x.clone(), 1);
+    let mut search = Search::new(m.clone(), vec![x.clone(), y.clone()]);
+    let sol = search.solve().unwrap();
+    assert_eq!((sol.get(&"x".to_string()), sol.get(&"y".to_string())), (&1, &0));
+}
+
+#[test]
+fn detects_unsatisfiable_model() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 1, "x");
+    let y = Var::new(m.clone(), 0, 1, "y");
+    let z = Var::new(m.clone(), 0, 1, "z");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    NeqXY::new(m.clone(), y.clone(), z.clone());
+    NeqXY::new(m.clone(), x.clone(), z.clone());
+    let mut search = Search::new(m.clone(), vec![x.clone(), y.clone(), z.clone()]);
+    assert!(search.solve().is_none());
+    assert_eq!(m.level(), 0);
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn restores_model_when_dropped() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 5, "x");
+    let y = Var::new(m.clone(), 0, 5, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    {
+        let mut search = Search::new(m.clone(), vec![y.clone(), x.clone()]);
+        assert!(search.solve().is_some());
+        assert!(x.is_instanciated() && y.is_instanciated());
+    }
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (0, 4, 1, 5));
+    assert_eq!(m.level(), 0);
+}
+
+#[test]
+fn enumerates_all_solutions() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 1, 3, "x");
+    let y = Var::new(m.clone(), 1, 3, "y");
+    let z = Var::new(m.clone(), 1, 3, "z");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    NeqXY::new(m.clone(), y.clone(), z.clone());
+    NeqXY::new(m.clone(), x.clone(), z.clone());
+    let mut sols: Vec<(int, int, int)> = m.solutions(vec![x.clone(), y.clone(), z.clone()])
+        .map(|sol| (*sol.get(&"x".to_string()), *sol.get(&"y".to_string()), *sol.get(&"z".to_string())))
+        .collect();
+    sols.sort();
+    assert_eq!(sols, vec![(1, 2, 3), (1, 3, 2), (2, 1, 3), (2, 3, 1), (3, 1, 2), (3, 2, 1)]);
+    assert_eq!(m.level(), 0);
+    assert_eq!((x.min(), x.max()), (1, 3));
+}
+
+#[test]
+fn counts_unique_solution() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 1, 3, "x");
+    let y = Var::new(m.clone(), 1, 3, "y");
+    let z = Var::new(m.clone(), 1, 3, "z");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    NeqXY::new(m.clone(), y.clone(), z.clone());
+    NeqXY::new(m.clone(), x.clone(), z.clone());
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone(), z.clone()]), 6);
+    EqXYC::new(m.clone(), z.clone(), x.clone(), 2);
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone(), z.clone()]), 1);
+}
+
+#[test]
+fn counts_no_solution() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 1, 3, "x");
+    LtXY::new(m.clone(), x.clone(), x.clone());
+    assert_eq!(m.count_solutions(vec![x.clone()]), 0);
+}
+
+#[test]
+fn minimizes_objective() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 1, "x");
+    let y = Var::new(m.clone(), 0, 1, "y");
+    let obj = Var::new(m.clone(), 0, 10, "obj");
+    NeqXY::new(m.clone(), x.clone(), y.clone());
+    GeXYC::new(m.clone(), obj.clone(), y.clone(), 2);
+    let mut search = m.solutions(vec![x.clone()]).minimize(obj.clone());
+    let values: Vec<int> = search.by_ref().map(|sol| *sol.get(&"obj".to_string())).collect();
+    assert_eq!(values, vec![3, 2]);
+    assert!(search.proved_optimal());
+}
+
+#[test]
+fn maximizes_objective() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 3, "x");
+    let y = Var::new(m.clone(), 0, 3, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    let mut search = m.solutions(vec![x.clone(), y.clone()]).maximize(x.clone());
+    assert!(!search.proved_optimal());
+    let values: Vec<int> = search.by_ref().map(|sol| *sol.get(&"x".to_string())).collect();
+    assert_eq!(values, vec![0, 1, 2]);
+    assert!(search.proved_optimal());
+    assert_eq!((x.min(), x.max()), (0, 2));
+}
+
+#[test]
+#[should_fail]
+fn rejects_views_named_as_variables() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 3, "x");
+    let y = Var::new(m.clone(), 0, 3, "-x");
+    Search::new(m.clone(), vec![x.clone(), y, x.neg()]);
+}
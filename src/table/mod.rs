@@ -0,0 +1,300 @@
+use super::{Event, Dom, Prop, Mod, FDVar, Propagator, Failure, TrailedWords, count_bits};
+
+use std::cell::Cell;
+use std::collections::hashmap::HashMap;
+use std::rc::{Rc, Weak};
+
+/// the values of the variables form one of the tuples
+pub struct Table;
+
+impl Table {
+    pub fn new(model: Rc<Mod>, vars: &[Rc<FDVar>], tuples: &[Vec<int>]) {
+        CompactTable::new(model, vars.to_vec(), tuples, true);
+    }
+}
+
+/// the values of the variables form none of the tuples
+pub struct NegativeTable;
+
+impl NegativeTable {
+    pub fn new(model: Rc<Mod>, vars: &[Rc<FDVar>], tuples: &[Vec<int>]) {
+        CompactTable::new(model, vars.to_vec(), tuples, false);
+    }
+}
+
+/// compact table, tuples are bits of a set updated from the domains
+struct CompactTable : Prop {
+    /// whether the tuples are the allowed or the forbidden ones
+    allowed: bool,
+    /// tuples whose values are all in the domains
+    current: Rc<TrailedWords>,
+    /// for each variable, the tuples having each value
+    supports: Vec<HashMap<int, Support>>,
+    /// for each variable, the values having tuples, sorted
+    values: Vec<Vec<int>>,
+    /// for each variable, the bounds of the intervals of its domain at the
+    /// last update, as words
+    last: Vec<Rc<TrailedWords>>,
+    /// size of each variable at the last update, 0 before the first one
+    sizes: Rc<TrailedWords>
+}
+
+/// tuples having a value
+struct Support {
+    tuples: Vec<u64>,
+    /// word where a tuple left was last found, not undone on backtrack
+    residue: Cell<uint>
+}
+
+impl CompactTable {
+    fn new(model: Rc<Mod>, vars: Vec<Rc<FDVar>>, tuples: &[Vec<int>], allowed: bool) {
+        let mut tuples = tuples.to_vec();
+        tuples.sort();
+        tuples.dedup();
+        assert!(tuples.iter().all(|t| t.len() == vars.len()),
+                "tuples must have one value per variable");
+        let n = tuples.len();
+        let words = (n + 63) / 64;
+        let mut supports: Vec<HashMap<int, Support>> =
+            Vec::from_fn(vars.len(), |_| HashMap::new());
+        for (k, t) in tuples.iter().enumerate() {
+            for (i, &v) in t.iter().enumerate() {
+                let support = supports.get_mut(i).find_or_insert_with(v, |_| {
+                    Support { tuples: Vec::from_elem(words, 0u64), residue: Cell::new(0) }
+                });
+                *support.tuples.get_mut(k / 64) |= 1 << (k % 64);
+            }
+        }
+        let values = supports.iter().map(|supports| {
+            let mut values: Vec<int> = supports.keys().map(|&v| v).collect();
+            values.sort();
+            values
+        }).collect();
+        let current = Vec::from_fn(words, |w| {
+            if w < words - 1 || n % 64 == 0 { !0u64 } else { (1 << (n % 64)) - 1 }
+        });
+        let id = model.propagators.borrow().len();
+        let this = CompactTable {
+            model: model.downgrade(),
+            id: id,
+            last: Vec::from_fn(vars.len(), |_| TrailedWords::new(Vec::new())),
+            sizes: TrailedWords::new(Vec::from_elem(vars.len(), 0u64)),
+            vars: vars,
+            allowed: allowed,
+            current: TrailedWords::new(current),
+            supports: supports,
+            values: values
+        };
+        let p = Rc::new((box this) as Box<Propagator>);
+        model.add_prop(p);
+    }
+
+    /// removes the tuples with a value no longer in the domains, only looking
+    /// at the variables which changed since the last update, returns them
+    /// and whether tuples were removed
+    fn update_table(&self, model: &Mod) -> (Vec<uint>, bool) {
+        let mut changed = Vec::new();
+        let mut removed = false;
+        for (i, x) in self.vars.iter().enumerate() {
+            let size = x.size();
+            let last = *self.sizes.words.borrow().get(i) as uint;
+            if size == last { continue; }
+            changed.push(i);
+            model.save_words(&self.sizes);
+            *self.sizes.words.borrow_mut().get_mut(i) = size as u64;
+            let intervals: Vec<(int, int)> = x.intervals().collect();
+            let supports = self.supports.get(i);
+            let mut current = self.current.words.borrow_mut();
+            let mut mask = Vec::from_elem(current.len(), 0u64);
+            let keep = if last > size && last - size < size {
+                // fewer values removed than left: the tuples of the values
+                // removed since the last update go
+                let previous = self.last_intervals(i);
+                let values = self.values.get(i);
+                for &(lo, hi) in difference(previous.as_slice(), intervals.as_slice()).iter() {
+                    for &v in values.slice_from(first_at_least(values.as_slice(), lo)).iter()
+                        .take_while(|&&v| v <= hi) {
+                        or_into(&mut mask, &supports.get(&v).tuples);
+                    }
+                }
+                false
+            } else {
+                // the tuples of the values left stay
+                for v in x.values() {
+                    match supports.find(&v) {
+                        Some(support) => or_into(&mut mask, &support.tuples),
+                        None => ()
+                    }
+                }
+                true
+            };
+            for (c, &m) in current.mut_iter().zip(mask.iter()) {
+                let old = *c;
+                *c &= if keep { m } else { !m };
+                removed = removed || *c != old;
+            }
+            self.set_last_intervals(model, i, intervals.as_slice());
+        }
+        (changed, removed)
+    }
+
+    /// intervals of the variable i at the last update
+    fn last_intervals(&self, i: uint) -> Vec<(int, int)> {
+        let words = self.last.get(i).words.borrow();
+        range(0, words.len() / 2)
+            .map(|k| (*words.get(2 * k) as int, *words.get(2 * k + 1) as int))
+            .collect()
+    }
+
+    fn set_last_intervals(&self, model: &Mod, i: uint, intervals: &[(int, int)]) {
+        let last = self.last.get(i);
+        model.save_words(last);
+        let mut words = last.words.borrow_mut();
+        words.clear();
+        for &(lo, hi) in intervals.iter() {
+            // the bits of the bounds, converted back by last_intervals
+            words.push(lo as u64);
+            words.push(hi as u64);
+        }
+    }
+
+    /// whether some tuple left has value v for the variable i, looking at
+    /// the residue first
+    fn is_supported(&self, i: uint, v: int) -> bool {
+        let support = match self.supports.get(i).find(&v) {
+            Some(support) => support,
+            None => return false
+        };
+        let current = self.current.words.borrow();
+        let r = support.residue.get();
+        if *current.get(r) & *support.tuples.get(r) != 0 { return true; }
+        match range(0, current.len()).find(|&w| *current.get(w) & *support.tuples.get(w) != 0) {
+            Some(w) => {
+                support.residue.set(w);
+                true
+            },
+            None => false
+        }
+    }
+
+    /// number of tuples left with value v for the variable i
+    fn count(&self, i: uint, v: int) -> uint {
+        match self.supports.get(i).find(&v) {
+            Some(support) => self.current.words.borrow().iter().zip(support.tuples.iter())
+                .fold(0, |n, (&c, &b)| n + count_bits(c & b)),
+            None => 0
+        }
+    }
+
+    /// keeps the values appearing in some tuple left, for the variables
+    /// given
+    fn filter_allowed(&self, vars: &[uint]) -> Result<Vec<uint>, Failure> {
+        if self.current.words.borrow().iter().all(|&w| w == 0) { return Err(Failure); }
+        let mut woken = Vec::new();
+        for &i in vars.iter() {
+            let x = self.vars.get(i);
+            let supported: Vec<int> = x.values().filter(|&v| self.is_supported(i, v)).collect();
+            woken.push_all(try!(x.restrict_to(supported.as_slice())).as_slice());
+        }
+        Ok(woken)
+    }
+
+    /// removes the values whose every combination with the other domains is
+    /// a tuple left
+    fn filter_forbidden(&self) -> Result<Vec<uint>, Failure> {
+        if self.current.words.borrow().iter().all(|&w| w == 0) {
+            self.entail();
+            return Ok(vec![]);
+        }
+        let left = self.current.words.borrow().iter().fold(0, |n, &w| n + count_bits(w));
+        // the sizes the tuples left were computed with
+        let sizes: Vec<uint> = self.vars.iter().map(|x| x.size()).collect();
+        let mut woken = Vec::new();
+        for (i, x) in self.vars.iter().enumerate() {
+            // number of combinations, as long as it does not exceed the tuples
+            let mut combinations = 1u;
+            for (j, &size) in sizes.iter().enumerate() {
+                if j != i && combinations <= left {
+                    combinations *= size;
+                }
+            }
+            if combinations > left { continue; }
+            let forbidden: Vec<int> = x.values()
+                .filter(|&v| self.count(i, v) == combinations)
+                .collect();
+            for &v in forbidden.iter() {
+                woken.push_all(try!(x.remove(v)).as_slice());
+            }
+        }
+        Ok(woken)
+    }
+}
+
+/// intervals of old not in new, new being included in old
+fn difference(old: &[(int, int)], new: &[(int, int)]) -> Vec<(int, int)> {
+    let mut removed = Vec::new();
+    let mut k = 0;
+    for &(lo, hi) in old.iter() {
+        let mut from = lo;
+        while k < new.len() {
+            let (start, end) = new[k];
+            if end > hi { break; }
+            if start > from { removed.push((from, start - 1)); }
+            from = end + 1;
+            k += 1;
+        }
+        if from <= hi { removed.push((from, hi)); }
+    }
+    removed
+}
+
+/// index of the first of the sorted values at least v
+fn first_at_least(values: &[int], v: int) -> uint {
+    let (mut lo, mut hi) = (0, values.len());
+    while lo < hi {
+        let mid = (lo + hi) / 2;
+        if values[mid] < v { lo = mid + 1; } else { hi = mid; }
+    }
+    lo
+}
+
+/// adds the bits of words to mask
+fn or_into(mask: &mut Vec<u64>, words: &Vec<u64>) {
+    for (m, &w) in mask.mut_iter().zip(words.iter()) { *m |= w; }
+}
+
+impl Propagator for CompactTable {
+    fn id(&self) -> uint {
+        self.id
+    }
+
+    fn model(&self) -> Weak<Mod> {
+        self.model.clone()
+    }
+
+    fn events(&self) -> Vec<(uint, Event)> {
+        self.vars.iter().map(|x| (x.id, Dom)).collect()
+    }
+
+    fn priority(&self) -> uint {
+        2
+    }
+
+    fn propagate(&self) -> Result<Vec<uint>, Failure> {
+        let model = self.model.upgrade().unwrap();
+        let first = self.sizes.words.borrow().iter().any(|&size| size == 0);
+        model.save_words(&self.current);
+        let (changed, removed) = self.update_table(&*model);
+        if !self.allowed { return self.filter_forbidden(); }
+        // values without tuples are removed at the first update, later on
+        // the values left lose tuples only if some were removed, and not
+        // when they were all removed with values of the variable itself
+        let vars: Vec<uint> = range(0, self.vars.len())
+            .filter(|&i| first || (removed && changed.as_slice() != [i].as_slice()))
+            .collect();
+        self.filter_allowed(vars.as_slice())
+    }
+}
+
+#[cfg(test)]
+mod tests;
@@ -0,0 +1,123 @@
+use super::super::{Model, Mod, Var, FDVar, GeXC, LeXC, NeqXC, Table, NegativeTable};
+use super::difference;
+
+use std::rc::Rc;
+
+fn setup_vars(n: uint, min: int, max: int) -> (Rc<Mod>, Vec<Rc<FDVar>>) {
+    let m = Model::new();
+    let vars = range(0, n)
+        .map(|i| Var::new(m.clone(), min, max, format!("x{}", i).as_slice()))
+        .collect();
+    (m, vars)
+}
+
+#[test]
+fn table_keeps_supported_values() {
+    let (m, vars) = setup_vars(3, 0, 3);
+    let tuples = [vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 0], vec![0, 3, 3]];
+    Table::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    let (x, y, z) = (vars.get(0), vars.get(1), vars.get(2));
+    assert_eq!((x.size(), y.size(), z.size()), (3, 3, 3));
+    assert!(!x.contains(3) && !y.contains(0) && !z.contains(1));
+    NeqXC::new(m.clone(), x.clone(), 0);
+    assert_eq!(y.intervals().collect::<Vec<(int, int)>>(), vec![(2, 3)]);
+    assert_eq!(z.intervals().collect::<Vec<(int, int)>>(), vec![(0, 0), (3, 3)]);
+}
+
+#[test]
+fn table_restores_tuples_on_backtrack() {
+    let (m, vars) = setup_vars(3, 0, 3);
+    let tuples = [vec![0, 1, 2], vec![1, 2, 3], vec![2, 3, 0], vec![0, 3, 3]];
+    Table::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    let (x, y, z) = (vars.get(0), vars.get(1), vars.get(2));
+    m.push_level();
+    GeXC::new(m.clone(), y.clone(), 2);
+    LeXC::new(m.clone(), y.clone(), 2);
+    assert_eq!((x.value(), z.value()), (Some(1), Some(3)));
+    m.pop_level();
+    m.push_level();
+    GeXC::new(m.clone(), y.clone(), 3);
+    assert_eq!((x.size(), z.size()), (2, 2));
+    LeXC::new(m.clone(), z.clone(), 0);
+    assert_eq!(x.value(), Some(2));
+    m.pop_level();
+    assert_eq!(m.count_solutions(vars.clone()), 4);
+}
+
+#[test]
+fn table_spans_several_words() {
+    let (m, vars) = setup_vars(2, 0, 20);
+    let mut tuples = Vec::new();
+    for a in range(0, 15) {
+        for b in range(a + 1, 15) {
+            tuples.push(vec![a, b]);
+        }
+    }
+    assert_eq!(tuples.len(), 105);
+    Table::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    let (x, y) = (vars.get(0), vars.get(1));
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (0, 13, 1, 14));
+    assert_eq!(m.count_solutions(vars.clone()), 105);
+    GeXC::new(m.clone(), x.clone(), 10);
+    assert_eq!(y.min(), 11);
+}
+
+#[test]
+fn table_fails_without_tuple() {
+    let (m, vars) = setup_vars(2, 0, 5);
+    Table::new(m.clone(), vars.as_slice(), &[vec![6, 1], vec![1, 7]]);
+    assert!(m.is_failed());
+}
+
+#[test]
+fn negative_table_removes_forbidden_values() {
+    let (m, vars) = setup_vars(2, 0, 1);
+    let (x, y) = (vars.get(0), vars.get(1));
+    let tuples = [vec![0, 0], vec![0, 1], vec![1, 1], vec![0, 0]];
+    NegativeTable::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    assert_eq!(x.value(), Some(1));
+    assert_eq!(y.value(), Some(0));
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn negative_table_counts_solutions() {
+    let (m, vars) = setup_vars(3, 0, 2);
+    let tuples = [vec![0, 0, 0], vec![1, 1, 1], vec![2, 2, 2], vec![0, 1, 2], vec![5, 5, 5]];
+    NegativeTable::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    assert_eq!(m.count_solutions(vars.clone()), 27 - 4);
+    LeXC::new(m.clone(), vars.get(0).clone(), 0);
+    LeXC::new(m.clone(), vars.get(1).clone(), 0);
+    assert!(!vars.get(2).contains(0));
+}
+
+#[test]
+fn table_removes_tuples_incrementally() {
+    let (m, vars) = setup_vars(2, 0, 99);
+    let tuples: Vec<Vec<int>> = range(0, 100).map(|a| vec![a, a]).collect();
+    Table::new(m.clone(), vars.as_slice(), tuples.as_slice());
+    let (x, y) = (vars.get(0), vars.get(1));
+    m.push_level();
+    for v in range(10, 20) {
+        NeqXC::new(m.clone(), x.clone(), v * 3);
+        assert!(!y.contains(v * 3) && y.contains(v * 3 + 1));
+    }
+    assert_eq!(y.size(), 90);
+    // most values removed at once
+    LeXC::new(m.clone(), x.clone(), 31);
+    assert_eq!(y.intervals().collect::<Vec<(int, int)>>(), vec![(0, 29), (31, 31)]);
+    m.pop_level();
+    assert_eq!(y.size(), 100);
+    NeqXC::new(m.clone(), y.clone(), 64);
+    assert!(!x.contains(64) && x.contains(63) && x.contains(65));
+}
+
+#[test]
+fn removed_intervals() {
+    let old = [(0, 10), (20, 30), (40, 40)];
+    assert_eq!(difference(old.as_slice(), &[(2, 3), (5, 10), (20, 30)]),
+               vec![(0, 1), (4, 4), (40, 40)]);
+    assert!(difference(old.as_slice(), old.as_slice()).is_empty());
+    assert_eq!(difference(old.as_slice(), &[(25, 25)]),
+               vec![(0, 10), (20, 24), (26, 30), (40, 40)]);
+}
@@ -1,4 +1,7 @@
-use super::{Model, Var, Domain, IntervalDomain, IntervalDom, BitDomain};
+use super::{Model, Var, FDVar, Domain, IntervalDomain, IntervalDom, BitDomain, BitSetDomain, SparseDomain, Bits, BitSet, Intervals, Adaptive, Failure, Min, Max, Ins, Dom};
+use super::{LtXY, LeXC, GeXC, NeqXC, InXS};
+
+use std::rc::Rc;
 
 use std::cell::RefCell;
 
@@ -13,6 +16,178 @@ fn creates_new_var() {
     assert_eq!(m.clone().vars.borrow().len(), 2);
 }
 
+#[test]
+fn pop_level_restores_domains() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), -2, 255, "x");
+    let y = Var::new(m.clone(), -2, 255, "y");
+    m.push_level();
+    LeXC::new(m.clone(), x.clone(), 12);
+    NeqXC::new(m.clone(), x.clone(), 3);
+    m.push_level();
+    GeXC::new(m.clone(), x.clone(), 5);
+    GeXC::new(m.clone(), y.clone(), 5);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (5, 12, 5, 255));
+    m.pop_level();
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (-2, 12, -2, 255));
+    assert_eq!(x.dom.intervals(), vec![(-2, 2), (4, 12)]);
+    m.pop_level();
+    assert_eq!((x.min(), x.max()), (-2, 255));
+    assert_eq!(x.dom.intervals(), vec![(-2, 255)]);
+    assert_eq!(m.level(), 0);
+}
+
+#[test]
+fn pop_level_restores_failure() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    m.push_level();
+    GeXC::new(m.clone(), x.clone(), 5);
+    LeXC::new(m.clone(), x.clone(), 4);
+    assert!(m.is_failed());
+    m.pop_level();
+    assert!(!m.is_failed());
+    assert_eq!((x.min(), x.max()), (0, 10));
+}
+
+#[test]
+fn pop_level_removes_posted_propagators() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    m.push_level();
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    assert_eq!((x.max(), y.min()), (9, 1));
+    m.pop_level();
+    assert_eq!(m.propagators.borrow().len(), 0);
+    assert_eq!(m.degree(x.id) + m.degree(y.id), 0);
+    LeXC::new(m.clone(), y.clone(), 3);
+    assert_eq!((x.min(), x.max()), (0, 10));
+    LtXY::new(m.clone(), y.clone(), x.clone());
+    assert_eq!((x.min(), y.max()), (1, 3));
+}
+
+#[test]
+fn del_waiting_removes_by_id() {
+    let m = Model::new();
+    for &p in [7, 3, 9].iter() {
+        m.add_waiting(0, Min, p);
+    }
+    m.del_waiting(0, Min, 9);
+    m.del_waiting(0, Min, 42);
+    m.del_waiting(1, Max, 3);
+    assert_eq!(m.get_waiting(0, Min), vec![7, 3]);
+    m.push_level();
+    m.del_waiting(0, Min, 7);
+    assert_eq!(m.get_waiting(0, Min), vec![3]);
+    m.pop_level();
+    assert_eq!(m.get_waiting(0, Min), vec![3, 7]);
+}
+
+#[test]
+fn pop_level_restores_entailed_propagators() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 20, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    assert_eq!(m.get_waiting(y.id, Max), vec![0]);
+    m.push_level();
+    LeXC::new(m.clone(), x.clone(), 3);
+    GeXC::new(m.clone(), y.clone(), 5);
+    LeXC::new(m.clone(), y.clone(), 15);
+    assert!(m.get_waiting(y.id, Max).is_empty());
+    m.pop_level();
+    assert_eq!(m.get_waiting(y.id, Max), vec![0]);
+    assert_eq!((x.min(), x.max(), y.min(), y.max()), (0, 10, 1, 20));
+}
+
+#[test]
+fn remove_wakes_dom_propagators() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    m.add_waiting(x.id, Min, 1);
+    m.add_waiting(x.id, Ins, 2);
+    m.add_waiting(x.id, Dom, 3);
+    assert_eq!(x.remove(5), Ok(vec![3]));
+    assert_eq!(x.remove(5), Ok(vec![]));
+    assert_eq!(x.remove(0), Ok(vec![1, 3]));
+    assert_eq!(x.set_max(1), Ok(vec![2, 3]));
+}
+
+#[test]
+fn bulk_operations_wake_once() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 20, "x");
+    m.add_waiting(x.id, Min, 1);
+    m.add_waiting(x.id, Max, 2);
+    m.add_waiting(x.id, Ins, 3);
+    m.add_waiting(x.id, Dom, 4);
+    assert_eq!(x.remove_range(4, 8), Ok(vec![4]));
+    assert_eq!(x.remove_range(5, 7), Ok(vec![]));
+    assert_eq!(x.intersect_with(&[(2, 6), (10, 12)]), Ok(vec![1, 2, 4]));
+    assert_eq!(x.restrict_to(&[2, 3, 10, 11, 12, 13]), Ok(vec![]));
+    assert_eq!(x.restrict_to(&[12, 0, 3]), Ok(vec![1, 4]));
+    assert_eq!(x.restrict_to(&[1, 2]), Err(Failure));
+}
+
+#[test]
+fn propagates_long_chains() {
+    let m = Model::new();
+    let vars: Vec<Rc<FDVar>> = range(0, 2000)
+        .map(|i| Var::new(m.clone(), 0, 5000, format!("x{}", i).as_slice()))
+        .collect();
+    for i in range(1, vars.len()) {
+        LtXY::new(m.clone(), vars.get(i - 1).clone(), vars.get(i).clone());
+    }
+    assert_eq!((vars.get(0).max(), vars.get(1999).min()), (3001, 1999));
+    GeXC::new(m.clone(), vars.get(0).clone(), 1000);
+    assert_eq!((vars.get(1999).min(), vars.get(1999).max()), (2999, 5000));
+    assert!(m.propagate_to_fixpoint().is_ok());
+    GeXC::new(m.clone(), vars.get(0).clone(), 3002);
+    assert!(m.is_failed());
+    assert!(m.propagate_to_fixpoint().is_err());
+}
+
+#[test]
+fn schedules_propagators_once() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = Var::new(m.clone(), 0, 10, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    m.schedule(&[1, 0, 1, 0]);
+    assert_eq!(m.unschedule(), Some(1));
+    assert_eq!(m.unschedule(), Some(0));
+    assert_eq!(m.unschedule(), None);
+}
+
+#[test]
+fn chooses_domain_representation() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), -2, 61, "x");
+    let y = Var::new(m.clone(), -2, 62, "y");
+    let z = Var::new_intervals(m.clone(), 0, 3, "z");
+    let t = Var::new_bits(m.clone(), 10, 20, "t");
+    assert!(match x.dom { Bits(_) => true, _ => false });
+    assert!(match y.dom { Intervals(_) => true, _ => false });
+    assert!(match z.dom { Intervals(_) => true, _ => false });
+    assert!(match t.dom { Bits(_) => true, _ => false });
+}
+
+#[test]
+fn propagates_on_bit_domains() {
+    let m = Model::new();
+    let x = Var::new_bits(m.clone(), 0, 10, "x");
+    let y = Var::new_intervals(m.clone(), 0, 10, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    m.push_level();
+    NeqXC::new(m.clone(), x.clone(), 5);
+    LeXC::new(m.clone(), y.clone(), 6);
+    assert_eq!((x.min(), x.max(), x.size()), (0, 4, 5));
+    m.pop_level();
+    assert_eq!((x.min(), x.max(), x.size()), (0, 9, 10));
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone()]), 55);
+}
+
 fn min_is_min(d: &IntervalDomain) -> bool {
     match d.dom.borrow().intervals.get(0) {
         &(x, _) => x == d.get_min()
@@ -44,7 +219,7 @@ fn intervals_bounds_are_coherent(d: &IntervalDomain) {
 #[test]
 fn sets_min_lower() {
     let d = setup_domain_simple();
-    d.set_min(-4);
+    assert_eq!(d.set_min(-4), Ok(false));
     assert_eq!(d.get_min(), -3);
     intervals_bounds_are_coherent(&d);
 }
@@ -57,7 +232,7 @@ fn sets_min_middle() {
     let mut v : int;
     for i in range(0, values.len()) {
         v = values[i];
-        d.set_min(v);
+        assert_eq!(d.set_min(v), Ok(true));
         assert_eq!(d.get_min(), v);
         assert_eq!(d.dom.borrow().intervals.len(), lengths[i])
     }
@@ -67,16 +242,15 @@ fn sets_min_middle() {
 #[test]
 fn sets_min_in_hole() {
     let d = setup_domain_simple();
-    d.set_min(43);
+    assert_eq!(d.set_min(43), Ok(true));
     assert_eq!(d.get_min(), 54);
     intervals_bounds_are_coherent(&d);
 }
 
 #[test]
-// #[should_fail]
 fn sets_min_too_high() {
     let d = setup_domain_simple();
-    d.set_min(65);
+    assert_eq!(d.set_min(65), Err(Failure));
     assert_eq!(d.get_min(), -3);
     intervals_bounds_are_coherent(&d);
 }
@@ -84,7 +258,7 @@ fn sets_min_too_high() {
 #[test]
 fn sets_max_higher() {
     let d = setup_domain_simple();
-    d.set_max(65);
+    assert_eq!(d.set_max(65), Ok(false));
     assert_eq!(d.get_max(), 64);
     intervals_bounds_are_coherent(&d);
 }
@@ -97,7 +271,7 @@ fn sets_max_middle() {
     let mut v : int;
     for i in range(0, values.len()) {
         v = values[i];
-        d.set_max(v);
+        assert_eq!(d.set_max(v), Ok(true));
         assert_eq!(d.get_max(), v);
         assert_eq!(d.dom.borrow().intervals.len(), lengths[i])
     }
@@ -107,16 +281,15 @@ fn sets_max_middle() {
 #[test]
 fn sets_max_in_hole() {
     let d = setup_domain_simple();
-    d.set_max(43);
+    assert_eq!(d.set_max(43), Ok(true));
     assert_eq!(d.get_max(), 42);
     intervals_bounds_are_coherent(&d);
 }
 
 #[test]
-// #[should_fail]
 fn sets_max_too_low() {
     let d = setup_domain_simple();
-    d.set_max(-4);
+    assert_eq!(d.set_max(-4), Err(Failure));
     assert_eq!(d.get_max(), 64);
     intervals_bounds_are_coherent(&d);
 }
@@ -136,13 +309,9 @@ fn setup_domain_holy() -> IntervalDomain {
 fn remove_outside() {
     let d = setup_domain_holy();
     let e = setup_domain_holy();
-    d.remove(-8);
-    d.remove(3);
-    d.remove(19);
-    d.remove(31);
-    d.remove(35);
-    d.remove(48);
-    d.remove(128);
+    for &v in [-8, 3, 19, 31, 35, 48, 128].iter() {
+        assert_eq!(d.remove(v), Ok(false));
+    }
     assert_eq!(d.dom.borrow().intervals.len(), e.dom.borrow().intervals.len());
     for i in range(0, d.dom.borrow().intervals.len()) {
         assert_eq!(d.dom.borrow().intervals.get(i), e.dom.borrow().intervals.get(i));
@@ -155,7 +324,7 @@ fn remove_inside() {
     let d = setup_domain_holy();
     let values = [-3, -1, 30, 36, 64];
     for &v in values.iter() {
-        d.remove(v)
+        assert_eq!(d.remove(v), Ok(true));
     }
     for &v in values.iter() {
         for &(x, y) in d.dom.borrow().intervals.iter() {
@@ -166,6 +335,23 @@ fn remove_inside() {
     intervals_bounds_are_coherent(&d);
 }
 
+#[test]
+fn remove_last_value_fails() {
+    let d: IntervalDomain = Domain::new(3, 4);
+    assert_eq!(d.remove(3), Ok(true));
+    assert_eq!(d.remove(4), Err(Failure));
+    assert_eq!((d.get_min(), d.get_max()), (4, 4));
+}
+
+#[test]
+fn remove_updates_bounds() {
+    let d = setup_domain_holy();
+    assert_eq!(d.set_max(30), Ok(true));
+    assert_eq!(d.remove(30), Ok(true));
+    assert_eq!(d.get_max(), 24);
+    intervals_bounds_are_coherent(&d);
+}
+
 #[test]
 #[should_fail]
 #[allow(unused_variable)]
@@ -199,43 +385,409 @@ fn bitdomain_is_consistent() {
 #[test]
 fn bitdomain_set_min_lower() {
     let d = setup_bitdomain_simple();
-    d.set_min(-8);
+    assert_eq!(d.set_min(-8), Ok(false));
     assert_bitdomain_has_same_bounds_as_simple(d, None, None);
 }
 
 #[test]
 fn bitdomain_set_min_middle() {
     let d = setup_bitdomain_simple();
-    d.set_min(8);
+    assert_eq!(d.set_min(8), Ok(true));
     assert_bitdomain_has_same_bounds_as_simple(d, Some(8), None);
 }
 
 #[test]
-// #[should_fail]
 fn bitdomain_set_min_above() {
     let d = setup_bitdomain_simple();
-    d.set_min(68);
+    assert_eq!(d.set_min(68), Err(Failure));
     assert_bitdomain_has_same_bounds_as_simple(d, None, None);
 }
 
 #[test]
 fn bitdomain_set_max_above() {
     let d = setup_bitdomain_simple();
-    d.set_max(68);
+    assert_eq!(d.set_max(68), Ok(false));
     assert_bitdomain_has_same_bounds_as_simple(d, None, None);
 }
 
 #[test]
 fn bitdomain_set_max_middle() {
     let d = setup_bitdomain_simple();
-    d.set_max(8);
+    assert_eq!(d.set_max(8), Ok(true));
     assert_bitdomain_has_same_bounds_as_simple(d, None, Some(8));
 }
 
 #[test]
-// #[should_fail]
 fn bitdomain_set_max_below() {
     let d = setup_bitdomain_simple();
-    d.set_max(-8);
+    assert_eq!(d.set_max(-8), Err(Failure));
     assert_bitdomain_has_same_bounds_as_simple(d, None, None);
 }
+
+#[test]
+fn bitdomain_bounds_skip_holes() {
+    let d = setup_bitdomain_simple();
+    assert_eq!(d.remove(0), Ok(true));
+    assert_eq!(d.remove(0), Ok(false));
+    assert_eq!(d.set_min(0), Ok(true));
+    assert_bitdomain_has_same_bounds_as_simple(d, Some(1), None);
+}
+
+#[test]
+fn bitdomain_remove_bounds() {
+    let d = setup_bitdomain_simple();
+    assert_eq!(d.remove(-4), Ok(true));
+    assert_eq!(d.remove(59), Ok(true));
+    assert_bitdomain_has_same_bounds_as_simple(d, Some(-3), Some(58));
+}
+
+#[test]
+fn bitdomain_remove_last_value_fails() {
+    let d: BitDomain = Domain::new(7, 7);
+    assert_eq!(d.remove(7), Err(Failure));
+}
+
+fn setup_bitsetdomain_holy() -> BitSetDomain {
+    let d: BitSetDomain = Domain::new(-10, 300);
+    for v in range(60, 200) {
+        assert_eq!(d.remove(v), Ok(true));
+    }
+    d
+}
+
+#[test]
+fn bitsetdomain_is_consistent() {
+    let d: BitSetDomain = Domain::new(-10, 300);
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (-10, 300, 311));
+    assert_eq!(d.intervals(), vec![(-10, 300)]);
+    let e: BitSetDomain = Domain::new(0, 127);
+    assert_eq!((e.get_min(), e.get_max(), e.size()), (0, 127, 128));
+}
+
+#[test]
+fn bitsetdomain_removes_inside() {
+    let d = setup_bitsetdomain_holy();
+    assert_eq!(d.remove(100), Ok(false));
+    assert_eq!(d.remove(250), Ok(true));
+    assert_eq!(d.size(), 311 - 141);
+    assert_eq!(d.intervals(), vec![(-10, 59), (200, 249), (251, 300)]);
+}
+
+#[test]
+fn bitsetdomain_bounds_skip_holes() {
+    let d = setup_bitsetdomain_holy();
+    assert_eq!(d.set_min(58), Ok(true));
+    assert_eq!(d.get_min(), 58);
+    assert_eq!(d.set_min(60), Ok(true));
+    assert_eq!(d.get_min(), 200);
+    assert_eq!(d.set_max(199), Err(Failure));
+    assert_eq!(d.set_max(301), Ok(false));
+    assert_eq!(d.set_max(250), Ok(true));
+    assert_eq!(d.get_max(), 250);
+    assert_eq!(d.remove(250), Ok(true));
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (200, 249, 50));
+}
+
+#[test]
+fn bitsetdomain_remove_bounds() {
+    let d = setup_bitsetdomain_holy();
+    assert_eq!(d.remove(-10), Ok(true));
+    assert_eq!(d.remove(300), Ok(true));
+    assert_eq!((d.get_min(), d.get_max()), (-9, 299));
+    let e: BitSetDomain = Domain::new(7, 7);
+    assert_eq!(e.remove(7), Err(Failure));
+}
+
+#[test]
+fn bitset_variables_backtrack() {
+    let m = Model::new();
+    let x = Var::new_bitset(m.clone(), 0, 1000, "x");
+    m.push_level();
+    NeqXC::new(m.clone(), x.clone(), 500);
+    GeXC::new(m.clone(), x.clone(), 130);
+    assert_eq!((x.min(), x.max(), x.size()), (130, 1000, 870));
+    m.pop_level();
+    assert_eq!((x.min(), x.max(), x.size()), (0, 1000, 1001));
+}
+
+#[test]
+fn adaptive_variables_switch_representation() {
+    let m = Model::new();
+    let x = Var::new_adaptive(m.clone(), -1000, 100000, "x");
+    let inner_is_intervals = |x: &FDVar| match x.dom {
+        Adaptive(ref d) => match *d.dom.borrow() { Intervals(_) => true, _ => false },
+        _ => false
+    };
+    let inner_is_bitset = |x: &FDVar| match x.dom {
+        Adaptive(ref d) => match *d.dom.borrow() { BitSet(_) => true, _ => false },
+        _ => false
+    };
+    let inner_is_bits = |x: &FDVar| match x.dom {
+        Adaptive(ref d) => match *d.dom.borrow() { Bits(_) => true, _ => false },
+        _ => false
+    };
+    assert!(inner_is_intervals(&*x));
+    NeqXC::new(m.clone(), x.clone(), -5);
+    m.push_level();
+    LeXC::new(m.clone(), x.clone(), 0);
+    assert!(inner_is_bitset(&*x));
+    assert_eq!(x.dom.intervals(), vec![(-1000, -6), (-4, 0)]);
+    m.push_level();
+    GeXC::new(m.clone(), x.clone(), -50);
+    NeqXC::new(m.clone(), x.clone(), -40);
+    assert!(inner_is_bits(&*x));
+    assert_eq!(x.dom.intervals(), vec![(-50, -41), (-39, -6), (-4, 0)]);
+    m.pop_level();
+    assert!(inner_is_bitset(&*x));
+    m.pop_level();
+    assert!(inner_is_intervals(&*x));
+    assert_eq!((x.min(), x.max(), x.size()), (-1000, 100000, 101000));
+}
+
+#[test]
+fn adaptive_variables_switch_back_to_intervals() {
+    let m = Model::new();
+    let x = Var::new_adaptive(m.clone(), 0, 100000, "x");
+    let inner_is_intervals = |x: &FDVar| match x.dom {
+        Adaptive(ref d) => match *d.dom.borrow() { Intervals(_) => true, _ => false },
+        _ => false
+    };
+    NeqXC::new(m.clone(), x.clone(), 10);
+    LeXC::new(m.clone(), x.clone(), 1000);
+    assert!(!inner_is_intervals(&*x));
+    m.push_level();
+    // a single run over 16 words
+    GeXC::new(m.clone(), x.clone(), 11);
+    assert!(inner_is_intervals(&*x));
+    assert_eq!(x.dom.intervals(), vec![(11, 1000)]);
+    NeqXC::new(m.clone(), x.clone(), 500);
+    assert!(!inner_is_intervals(&*x));
+    assert_eq!(x.dom.intervals(), vec![(11, 499), (501, 1000)]);
+    m.pop_level();
+    assert!(!inner_is_intervals(&*x));
+    assert_eq!(x.dom.intervals(), vec![(0, 9), (11, 1000)]);
+}
+
+#[test]
+fn sparsedomain_removes_values() {
+    let d: SparseDomain = Domain::new(-3, 12);
+    assert_eq!(d.remove(4), Ok(true));
+    assert_eq!(d.remove(4), Ok(false));
+    assert_eq!(d.remove(20), Ok(false));
+    assert_eq!(d.remove(5), Ok(true));
+    assert_eq!(d.set_min(3), Ok(true));
+    assert_eq!(d.get_min(), 3);
+    assert_eq!(d.set_min(4), Ok(true));
+    assert_eq!(d.get_min(), 6);
+    assert_eq!(d.set_max(5), Err(Failure));
+    assert_eq!(d.remove(12), Ok(true));
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (6, 11, 6));
+    assert_eq!(d.intervals(), vec![(6, 11)]);
+}
+
+#[test]
+fn sparsedomain_restores_size_only() {
+    let d: SparseDomain = Domain::new(0, 9);
+    assert_eq!(d.remove(3), Ok(true));
+    let saved = d.clone();
+    for &v in [7, 0, 5, 9, 1].iter() {
+        assert_eq!(d.remove(v), Ok(true));
+    }
+    assert_eq!(d.intervals(), vec![(2, 2), (4, 4), (6, 6), (8, 8)]);
+    d.restore(&saved);
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (0, 9, 9));
+    assert_eq!(d.intervals(), vec![(0, 2), (4, 9)]);
+}
+
+#[test]
+fn sparsedomain_scans_few_values() {
+    let d: SparseDomain = Domain::new(0, 100000);
+    for v in range(0, 100001) {
+        if v % 10000 != 0 { assert_eq!(d.remove(v), Ok(true)); }
+    }
+    assert_eq!(d.size(), 11);
+    let saved = d.clone();
+    assert_eq!(d.set_min(15000), Ok(true));
+    assert_eq!(d.set_max(85000), Ok(true));
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (20000, 80000, 7));
+    assert_eq!(d.set_max(10000), Err(Failure));
+    d.restore(&saved);
+    assert_eq!((d.get_min(), d.get_max(), d.size()), (0, 100000, 11));
+    assert_eq!(d.set_min(99999), Ok(true));
+    assert_eq!(d.intervals(), vec![(100000, 100000)]);
+}
+
+#[test]
+fn sparse_variables_iterate_few_values() {
+    let m = Model::new();
+    let x = Var::new_sparse(m.clone(), 0, 1000000, "x");
+    InXS::new(m.clone(), x.clone(), &[3, 500000, 999999, 1000000]);
+    assert_eq!(x.values().collect::<Vec<int>>(), vec![3, 500000, 999999, 1000000]);
+    assert_eq!(x.next_value_after(4), Some(500000));
+    assert_eq!(x.prev_value_before(999999), Some(500000));
+    assert_eq!(x.prev_value_before(-7), None);
+    assert_eq!(x.next_value_after(1000000), None);
+}
+
+#[test]
+fn sparse_variables_backtrack() {
+    let m = Model::new();
+    let x = Var::new_sparse(m.clone(), 0, 20, "x");
+    let y = Var::new_sparse(m.clone(), 0, 20, "y");
+    LtXY::new(m.clone(), x.clone(), y.clone());
+    m.push_level();
+    NeqXC::new(m.clone(), y.clone(), 10);
+    LeXC::new(m.clone(), y.clone(), 11);
+    assert_eq!((x.min(), x.max(), y.size()), (0, 10, 10));
+    m.pop_level();
+    assert_eq!((x.min(), x.max(), y.size()), (0, 19, 20));
+    assert_eq!(m.count_solutions(vec![x.clone(), y.clone()]), 210);
+}
+
+/// the same domain in all representations
+fn setup_variables_holy() -> Vec<Rc<FDVar>> {
+    let m = Model::new();
+    let vars = vec![Var::new_intervals(m.clone(), -3, 40, "intervals"),
+                    Var::new_bits(m.clone(), -3, 40, "bits"),
+                    Var::new_bitset(m.clone(), -3, 40, "bitset"),
+                    Var::new_sparse(m.clone(), -3, 40, "sparse"),
+                    Var::new_adaptive(m.clone(), -3, 40, "adaptive")];
+    for x in vars.iter() {
+        for &v in [-3, 3, 4, 5, 17, 30, 31, 38, 40].iter() {
+            NeqXC::new(m.clone(), x.clone(), v);
+        }
+    }
+    vars
+}
+
+#[test]
+fn queries_all_domains() {
+    let expected = vec![(-2, 2), (6, 16), (18, 29), (32, 37), (39, 39)];
+    for x in setup_variables_holy().iter() {
+        assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), expected);
+        assert_eq!((x.min(), x.max(), x.size()), (-2, 39, 35));
+        assert!(!x.is_fixed());
+        assert_eq!(x.value(), None);
+        let values: Vec<int> = x.values().collect();
+        assert_eq!(values.len(), 35);
+        for v in range(-10, 50) {
+            let present = expected.iter().any(|&(lo, hi)| lo <= v && v <= hi);
+            assert_eq!(x.contains(v), present);
+            assert_eq!(values.contains(&v), present);
+            assert_eq!(x.next_value_after(v), values.iter().map(|&w| w).find(|&w| w > v));
+            assert_eq!(x.prev_value_before(v), values.iter().rev().map(|&w| w).find(|&w| w < v));
+        }
+    }
+}
+
+#[test]
+fn bitset_intervals_span_words() {
+    let d: BitSetDomain = Domain::new(0, 299);
+    assert_eq!(d.intervals(), vec![(0, 299)]);
+    assert_eq!(d.remove(63), Ok(true));
+    assert_eq!(d.remove(200), Ok(true));
+    assert_eq!(d.set_max(255), Ok(true));
+    assert_eq!(d.intervals(), vec![(0, 62), (64, 199), (201, 255)]);
+    assert_eq!(d.set_min(150), Ok(true));
+    assert_eq!(d.remove(201), Ok(true));
+    assert_eq!(d.intervals(), vec![(150, 199), (202, 255)]);
+}
+
+#[test]
+fn queries_fixed_variable() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 3, 3, "x");
+    assert!(x.is_fixed());
+    assert_eq!(x.value(), Some(3));
+    assert_eq!(x.values().collect::<Vec<int>>(), vec![3]);
+    assert_eq!(x.name(), "x");
+}
+
+#[test]
+fn bulk_operations_all_domains() {
+    for x in setup_variables_holy().iter() {
+        assert_eq!(x.remove_range(-10, 0), Ok(vec![]));
+        assert_eq!(x.remove_range(10, 20), Ok(vec![]));
+        assert_eq!(x.intervals().collect::<Vec<(int, int)>>(),
+                   vec![(1, 2), (6, 9), (21, 29), (32, 37), (39, 39)]);
+        assert_eq!(x.intersect_with(&[(0, 7), (25, 33), (36, 50)]), Ok(vec![]));
+        assert_eq!(x.intervals().collect::<Vec<(int, int)>>(),
+                   vec![(1, 2), (6, 7), (25, 29), (32, 33), (36, 37), (39, 39)]);
+        assert_eq!(x.restrict_to(&[39, 2, 26, 3, 27, 7, 6]), Ok(vec![]));
+        assert_eq!(x.intervals().collect::<Vec<(int, int)>>(),
+                   vec![(2, 2), (6, 7), (26, 27), (39, 39)]);
+        assert_eq!(x.size(), 6);
+        assert_eq!(x.remove_range(0, 50), Err(Failure));
+    }
+}
+
+#[test]
+fn views_read_variable() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    NeqXC::new(m.clone(), x.clone(), 3);
+    let y = x.offset(5);
+    assert_eq!((y.min(), y.max(), y.size()), (5, 15, 10));
+    assert!(y.contains(7) && !y.contains(8) && !y.contains(4));
+    assert_eq!(y.intervals().collect::<Vec<(int, int)>>(), vec![(5, 7), (9, 15)]);
+    let z = x.neg();
+    assert_eq!((z.min(), z.max()), (-10, 0));
+    assert_eq!(z.intervals().collect::<Vec<(int, int)>>(), vec![(-10, -4), (-2, 0)]);
+    assert_eq!((z.next_value_after(-4), z.prev_value_before(-2)), (Some(-2), Some(-4)));
+    let w = x.scale(2);
+    assert_eq!((w.min(), w.max()), (0, 20));
+    assert!(w.contains(4) && !w.contains(5) && !w.contains(6));
+    assert_eq!((w.next_value_after(5), w.prev_value_before(8)), (Some(8), Some(4)));
+    let v = x.scale(-3);
+    assert_eq!(v.values().collect::<Vec<int>>(),
+               vec![-30, -27, -24, -21, -18, -15, -12, -6, -3, 0]);
+    assert_eq!((v.next_value_after(-10), v.prev_value_before(-10)), (Some(-6), Some(-12)));
+}
+
+#[test]
+fn views_modify_variable() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    LeXC::new(m.clone(), x.offset(5), 12);
+    assert_eq!(x.max(), 7);
+    GeXC::new(m.clone(), x.neg(), -5);
+    assert_eq!(x.max(), 5);
+    NeqXC::new(m.clone(), x.scale(2), 3);
+    NeqXC::new(m.clone(), x.scale(2), 4);
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), vec![(0, 1), (3, 5)]);
+    GeXC::new(m.clone(), x.scale(-2), -7);
+    assert_eq!(x.max(), 3);
+    InXS::new(m.clone(), x.scale(3).offset(1), &[1, 2, 10, 11]);
+    assert_eq!(x.intervals().collect::<Vec<(int, int)>>(), vec![(0, 0), (3, 3)]);
+    assert!(!m.is_failed());
+}
+
+#[test]
+fn views_wake_propagators() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 20, "x");
+    let y = Var::new(m.clone(), 0, 20, "y");
+    LtXY::new(m.clone(), x.clone(), y.offset(3));
+    LeXC::new(m.clone(), y.clone(), 5);
+    assert_eq!(x.max(), 7);
+    let z = Var::new(m.clone(), -20, 20, "z");
+    LtXY::new(m.clone(), x.neg(), z.clone());
+    assert_eq!(z.min(), -6);
+    LeXC::new(m.clone(), x.clone(), 4);
+    assert_eq!(z.min(), -3);
+}
+
+#[test]
+fn views_of_views_backtrack() {
+    let m = Model::new();
+    let x = Var::new(m.clone(), 0, 10, "x");
+    let y = x.offset(2).neg();
+    assert_eq!((y.min(), y.max()), (-12, -2));
+    assert_eq!(y.name(), "-x + 2");
+    m.push_level();
+    LeXC::new(m.clone(), y.clone(), -5);
+    assert_eq!((x.min(), y.max()), (3, -5));
+    m.pop_level();
+    assert_eq!((x.min(), y.max()), (0, -2));
+    assert_eq!(m.count_solutions(vec![x.scale(3)]), 11);
+}